@@ -1,14 +1,22 @@
-use futures::stream::TryStreamExt;
+use futures::stream::{Stream, StreamExt, TryStreamExt};
 pub use mongodb::bson;
+pub use mongodb::Cursor;
 use mongodb::{
-    bson::{doc, Document},
-    options::ClientOptions,
-    Client,
+    bson::{doc, Bson, Document},
+    error::{ErrorKind, WriteFailure},
+    options::{ClientOptions, ConnectionString, HostInfo, Tls, TlsOptions},
+    results::CollectionType,
+    Client, Database,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
+/// Documents sampled by [`MongoCore::get_collection_schema`].
+const SCHEMA_SAMPLE_SIZE: i32 = 50;
+
 #[derive(Clone, Debug)]
 pub struct MongoCore {
     pub client: Arc<Mutex<Option<Client>>>,
@@ -23,21 +31,111 @@ impl Default for MongoCore {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CollectionInfo {
     pub name: String,
+    /// `collection`, `view` or `timeseries`, as reported by `listCollections`.
+    #[serde(default)]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub capped: bool,
+    /// Filled by [`MongoCore::collection_metadata`]; `None` until then or for views.
+    #[serde(default)]
+    pub estimated_count: Option<u64>,
+}
+
+impl CollectionInfo {
+    /// Short marker for collections that are not plain collections.
+    pub fn annotation(&self) -> Option<&str> {
+        if self.capped {
+            return Some("capped");
+        }
+        match self.kind.as_deref() {
+            Some("collection") | None => None,
+            Some(kind) => Some(kind),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct FindOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub filter: Option<Document>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub projection: Option<Document>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sort: Option<Document>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub limit: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub skip: Option<u64>,
 }
 
+/// Settings applied on top of what the connection string says. Unset fields
+/// leave the URI's own value alone.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConnectOptions {
+    pub max_pool_size: Option<u32>,
+    /// Turns TLS on with default settings; `false` keeps the URI's choice.
+    pub tls: bool,
+    /// Database holding the user's credentials; ignored without a username.
+    pub auth_source: Option<String>,
+    pub app_name: Option<String>,
+}
+
+impl ConnectOptions {
+    /// Parses `uri` and applies the overrides.
+    async fn client_options(&self, uri: &str) -> anyhow::Result<ClientOptions> {
+        let mut client_options = ClientOptions::parse(uri).await?;
+        if self.max_pool_size.is_some() {
+            client_options.max_pool_size = self.max_pool_size;
+        }
+        // A URI that already enables TLS keeps its own settings (CA file, ...)
+        if self.tls && !matches!(client_options.tls, Some(Tls::Enabled(_))) {
+            client_options.tls = Some(Tls::Enabled(TlsOptions::default()));
+        }
+        if let (Some(source), Some(credential)) =
+            (&self.auth_source, client_options.credential.as_mut())
+        {
+            credential.source = Some(source.clone());
+        }
+        if self.app_name.is_some() {
+            client_options.app_name = self.app_name.clone();
+        }
+        Ok(client_options)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct DatabaseInfo {
     pub name: String,
     pub collections: Vec<CollectionInfo>,
+    /// Filled by [`MongoCore::database_stats`] once the database is expanded.
+    #[serde(default)]
+    pub stats: Option<DatabaseStats>,
+}
+
+/// Sizes from `dbStats`. Fields the server didn't report (or the whole reply,
+/// when the user may not run `dbStats`) are `None`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DatabaseStats {
+    /// Storage plus index size on disk.
+    pub total_size: Option<u64>,
+    pub collections: Option<u64>,
+}
+
+/// Size of a result set, as returned by [`MongoCore::count`].
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DocumentCount {
+    pub value: u64,
+    /// Read from collection metadata instead of counted.
+    pub estimated: bool,
+}
+
+impl DocumentCount {
+    pub fn exact(value: u64) -> Self {
+        Self {
+            value,
+            estimated: false,
+        }
+    }
 }
 
 impl MongoCore {
@@ -47,36 +145,190 @@ impl MongoCore {
         }
     }
 
-    pub async fn connect(&self, uri: &str) -> anyhow::Result<()> {
-        let client_options = ClientOptions::parse(uri).await?;
+    pub async fn connect(&self, uri: &str, options: &ConnectOptions) -> anyhow::Result<()> {
+        let client_options = options.client_options(uri).await?;
         let client = Client::with_options(client_options)?;
         let mut guard = self.client.lock().await;
         *guard = Some(client);
         Ok(())
     }
 
+    /// Checks that `uri` reaches a server without touching the current client:
+    /// connects with a throwaway client, runs `{ping: 1}` on admin and returns
+    /// the round-trip time. Fails once `timeout` elapses.
+    pub async fn ping(
+        &self,
+        uri: &str,
+        options: &ConnectOptions,
+        timeout: Duration,
+    ) -> anyhow::Result<Duration> {
+        let attempt = async {
+            let mut client_options = options.client_options(uri).await?;
+            client_options.server_selection_timeout = Some(timeout);
+            client_options.connect_timeout = Some(timeout);
+            let client = Client::with_options(client_options)?;
+            let started = Instant::now();
+            client
+                .database("admin")
+                .run_command(doc! { "ping": 1 })
+                .await?;
+            let latency = started.elapsed();
+            client.shutdown().await;
+            anyhow::Ok(latency)
+        };
+        tokio::time::timeout(timeout, attempt)
+            .await
+            .map_err(|_| anyhow::anyhow!("No answer within {:?}", timeout))?
+    }
+
+    /// Whether a client has been set up by [`MongoCore::connect`].
+    pub async fn is_connected(&self) -> bool {
+        self.client.lock().await.is_some()
+    }
+
+    /// Runs `{ping: 1}` on admin through the current client and returns the
+    /// round-trip time. Fails when not connected or once `timeout` elapses.
+    pub async fn ping_current(&self, timeout: Duration) -> anyhow::Result<Duration> {
+        // The client is a cheap handle; cloning it keeps the lock free meanwhile
+        let Some(client) = self.client.lock().await.clone() else {
+            anyhow::bail!("Not connected to a MongoDB server");
+        };
+        let attempt = async {
+            let started = Instant::now();
+            client
+                .database("admin")
+                .run_command(doc! { "ping": 1 })
+                .await?;
+            anyhow::Ok(started.elapsed())
+        };
+        tokio::time::timeout(timeout, attempt)
+            .await
+            .map_err(|_| anyhow::anyhow!("No answer within {:?}", timeout))?
+    }
+
+    /// The hosts of a URI as `host:port` joined by commas, or the SRV record
+    /// name for `mongodb+srv://` URIs. Invalid URIs yield `None`.
+    pub fn uri_hosts(uri: &str) -> Option<String> {
+        match ConnectionString::parse(uri).ok()?.host_info {
+            HostInfo::HostIdentifiers(hosts) => Some(
+                hosts
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            HostInfo::DnsRecord(record) => Some(record),
+            _ => None,
+        }
+    }
+
+    /// The database named in the URI path (`mongodb://host/<db>`), if any.
+    /// Invalid URIs and URIs without a path yield `None`.
+    pub fn uri_default_database(uri: &str) -> Option<String> {
+        ConnectionString::parse(uri)
+            .ok()?
+            .default_database
+            .filter(|db| !db.is_empty())
+    }
+
     pub async fn list_databases(&self) -> anyhow::Result<Vec<DatabaseInfo>> {
         let guard = self.client.lock().await;
         let Some(client) = &*guard else {
             return Ok(vec![]);
         };
 
-        let db_names = client.list_database_names().await?;
-        let mut databases = Vec::new();
-
-        for db_name in db_names {
-            let db = client.database(&db_name);
-            let collection_names = db.list_collection_names().await?;
-            let collections = collection_names
-                .into_iter()
-                .map(|name| CollectionInfo { name })
-                .collect();
-            databases.push(DatabaseInfo {
+        // Users without listDatabases on the cluster still see the databases
+        // they hold privileges on.
+        let db_names = client
+            .list_database_names()
+            .authorized_databases(true)
+            .await
+            .map_err(|e| {
+                if is_auth_error(&e) {
+                    anyhow::anyhow!("Not authorized to list databases: {}", e)
+                } else {
+                    e.into()
+                }
+            })?;
+        // Collections are listed for every database at once. A database the
+        // user can't read shows up empty instead of failing the whole list.
+        let listings = db_names.into_iter().map(|db_name| async move {
+            let collections = match list_collection_infos(&client.database(&db_name)).await {
+                Ok(collections) => collections,
+                Err(e)
+                    if e.downcast_ref::<mongodb::error::Error>()
+                        .is_some_and(is_auth_error) =>
+                {
+                    vec![]
+                }
+                Err(e) => return Err(e),
+            };
+            Ok(DatabaseInfo {
                 name: db_name,
                 collections,
-            });
+                stats: None,
+            })
+        });
+        futures::future::join_all(listings)
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    /// Lists the collections of one database along with their estimated
+    /// document counts. Counts are skipped for views and on errors.
+    pub async fn collection_metadata(&self, db_name: &str) -> anyhow::Result<Vec<CollectionInfo>> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            return Ok(vec![]);
+        };
+
+        let db = client.database(db_name);
+        let mut collections = list_collection_infos(&db).await?;
+        for info in collections.iter_mut() {
+            if info.kind.as_deref() == Some("view") {
+                continue;
+            }
+            info.estimated_count = db
+                .collection::<Document>(&info.name)
+                .estimated_document_count()
+                .await
+                .ok();
         }
-        Ok(databases)
+        Ok(collections)
+    }
+
+    /// Runs an arbitrary database command and returns the server reply.
+    pub async fn run_command(&self, db_name: &str, command: Document) -> anyhow::Result<Document> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            anyhow::bail!("Not connected to a MongoDB server");
+        };
+
+        let reply = client.database(db_name).run_command(command).await?;
+        Ok(reply)
+    }
+
+    /// Recent lines of the server's in-memory `global` log, oldest first.
+    pub async fn server_log(&self) -> anyhow::Result<Vec<String>> {
+        let reply = self
+            .run_command("admin", doc! { "getLog": "global" })
+            .await
+            .map_err(|e| match e.downcast_ref::<mongodb::error::Error>() {
+                Some(err) if is_auth_error(err) => anyhow::anyhow!(
+                    "Not authorized to read the server log (getLog needs clusterMonitor on admin)"
+                ),
+                _ => e,
+            })?;
+        let lines = reply
+            .get_array("log")
+            .map(|log| {
+                log.iter()
+                    .filter_map(|line| line.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(lines)
     }
 
     pub async fn find_documents(
@@ -85,9 +337,78 @@ impl MongoCore {
         collection_name: &str,
         options: FindOptions,
     ) -> anyhow::Result<Vec<Document>> {
+        self.find_documents_streaming(db_name, collection_name, options, usize::MAX)
+            .await
+    }
+
+    /// Like [`MongoCore::find_documents`], but stops reading the cursor once
+    /// `max_docs` documents have been collected. Anything past the cap is
+    /// dropped with the cursor, so the result may be truncated: callers that
+    /// need to know whether more exist should ask for one extra document.
+    pub async fn find_documents_streaming(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+        options: FindOptions,
+        max_docs: usize,
+    ) -> anyhow::Result<Vec<Document>> {
+        if !self.is_connected().await {
+            return Ok(vec![]);
+        }
+        let mut cursor = self.find_cursor(db_name, collection_name, options).await?;
+        let mut docs = Vec::new();
+
+        while docs.len() < max_docs {
+            let Some(doc) = cursor.try_next().await? else {
+                break;
+            };
+            docs.push(doc);
+        }
+
+        Ok(docs)
+    }
+
+    /// Opens a change stream on a collection. Each item is a change event
+    /// as a document (`operationType`, `ns`, `fullDocument`, ...). Only the
+    /// opening holds the client lock, like [`MongoCore::find_cursor`].
+    /// Standalone servers have no change streams; that is reported here
+    /// instead of leaving the stream waiting forever.
+    pub async fn watch_collection(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+    ) -> anyhow::Result<impl Stream<Item = anyhow::Result<Document>>> {
         let guard = self.client.lock().await;
         let Some(client) = &*guard else {
-            return Ok(vec![]);
+            anyhow::bail!("Not connected to a MongoDB server");
+        };
+
+        let collection = client
+            .database(db_name)
+            .collection::<Document>(collection_name);
+        let stream = collection.watch().await.map_err(|e| {
+            if is_not_replica_set(&e) {
+                anyhow::anyhow!("Change streams need a replica set or sharded cluster")
+            } else {
+                e.into()
+            }
+        })?;
+
+        Ok(stream.map(|event| Ok(bson::to_document(&event?)?)))
+    }
+
+    /// Opens a find cursor for callers that process documents as they
+    /// arrive, such as exports. The client lock is only held while the
+    /// cursor is created.
+    pub async fn find_cursor(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+        options: FindOptions,
+    ) -> anyhow::Result<Cursor<Document>> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            anyhow::bail!("Not connected to a MongoDB server");
         };
 
         let db = client.database(db_name);
@@ -107,14 +428,7 @@ impl MongoCore {
             find = find.skip(skip);
         }
 
-        let mut cursor = find.await?;
-        let mut docs = Vec::new();
-
-        while let Some(doc) = cursor.try_next().await? {
-            docs.push(doc);
-        }
-
-        Ok(docs)
+        Ok(find.await?)
     }
 
     pub async fn count_documents(
@@ -136,6 +450,125 @@ impl MongoCore {
         Ok(count)
     }
 
+    /// Counts the documents matching `filter`. An exact count scans the whole
+    /// collection, so without a filter the estimate from collection metadata
+    /// is used instead; views, which have no metadata, are still counted.
+    pub async fn count(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+        filter: Option<Document>,
+    ) -> anyhow::Result<DocumentCount> {
+        let filter = filter.filter(|filter| !filter.is_empty());
+        if filter.is_none() {
+            let guard = self.client.lock().await;
+            let Some(client) = &*guard else {
+                return Ok(DocumentCount::default());
+            };
+            let estimate = client
+                .database(db_name)
+                .collection::<Document>(collection_name)
+                .estimated_document_count()
+                .await;
+            if let Ok(value) = estimate {
+                return Ok(DocumentCount {
+                    value,
+                    estimated: true,
+                });
+            }
+        }
+        let value = self
+            .count_documents(db_name, collection_name, filter)
+            .await?;
+        Ok(DocumentCount::exact(value))
+    }
+
+    /// Runs an aggregation pipeline on a collection and collects the results.
+    pub async fn run_aggregate(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+        pipeline: Vec<Document>,
+    ) -> anyhow::Result<Vec<Document>> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            anyhow::bail!("Not connected to a MongoDB server");
+        };
+
+        let db = client.database(db_name);
+        let collection = db.collection::<Document>(collection_name);
+
+        let docs = collection.aggregate(pipeline).await?.try_collect().await?;
+        Ok(docs)
+    }
+
+    /// Distinct values of `field` among the documents matching `filter`.
+    pub async fn distinct_values(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+        field: &str,
+        filter: Option<Document>,
+    ) -> anyhow::Result<Vec<Bson>> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            anyhow::bail!("Not connected to a MongoDB server");
+        };
+
+        let collection = client
+            .database(db_name)
+            .collection::<Document>(collection_name);
+        let values = collection
+            .distinct(field, filter.unwrap_or_default())
+            .await?;
+        Ok(values)
+    }
+
+    /// Index specs of a collection as the server reports them. The `key`
+    /// document keeps the field order of compound indexes.
+    pub async fn list_indexes(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+    ) -> anyhow::Result<Vec<Document>> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            anyhow::bail!("Not connected to a MongoDB server");
+        };
+
+        let collection = client
+            .database(db_name)
+            .collection::<Document>(collection_name);
+        let models: Vec<_> = collection.list_indexes().await?.try_collect().await?;
+        let specs = models
+            .iter()
+            .map(bson::to_document)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(specs)
+    }
+
+    /// Picks `size` random documents from a collection using `$sample`.
+    pub async fn sample_documents(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+        size: i64,
+    ) -> anyhow::Result<Vec<Document>> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            return Ok(vec![]);
+        };
+
+        let db = client.database(db_name);
+        let collection = db.collection::<Document>(collection_name);
+
+        let pipeline = vec![doc! { "$sample": { "size": size } }];
+        let docs = collection.aggregate(pipeline).await?.try_collect().await?;
+        Ok(docs)
+    }
+
+    /// Field names found in a random sample of the collection, in the order
+    /// they first appear.
     pub async fn get_collection_schema(
         &self,
         db_name: &str,
@@ -149,14 +582,539 @@ impl MongoCore {
         let db = client.database(db_name);
         let collection = db.collection::<Document>(collection_name);
 
-        let pipeline = vec![doc! { "$sample": { "size": 1 } }];
+        let pipeline = vec![doc! { "$sample": { "size": SCHEMA_SAMPLE_SIZE } }];
         let mut cursor = collection.aggregate(pipeline).await?;
 
-        if let Some(doc) = cursor.try_next().await? {
-            let keys: Vec<String> = doc.keys().map(|k| k.to_string()).collect();
-            return Ok(keys);
+        let mut keys: Vec<String> = vec![];
+        while let Some(doc) = cursor.try_next().await? {
+            for key in doc.keys() {
+                if !keys.contains(key) {
+                    keys.push(key.to_string());
+                }
+            }
         }
 
-        Ok(vec![])
+        Ok(keys)
+    }
+
+    /// Dotted paths found in a random sample of `sample_size` documents, in
+    /// the order they first appear, each with its most common BSON type.
+    /// Fields inside arrays of documents are reported under the array's path,
+    /// as MongoDB queries them (`items.sku`). Documents missing a field don't
+    /// count towards its type, and `null` only wins when no other type was seen.
+    pub async fn infer_schema(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+        sample_size: i64,
+    ) -> anyhow::Result<Vec<(String, String)>> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            return Ok(vec![]);
+        };
+
+        let collection = client
+            .database(db_name)
+            .collection::<Document>(collection_name);
+        let pipeline = vec![doc! { "$sample": { "size": sample_size } }];
+        let mut cursor = collection.aggregate(pipeline).await?;
+
+        let mut paths: Vec<String> = vec![];
+        // Per path, how many documents had each type, in first-seen order
+        let mut types: HashMap<String, Vec<(&'static str, usize)>> = HashMap::new();
+        while let Some(doc) = cursor.try_next().await? {
+            let mut found = vec![];
+            collect_paths("", &doc, &mut found);
+            // A path repeated inside an array still counts once per document
+            let mut counted = HashSet::new();
+            for (path, type_name) in found {
+                if !counted.insert((path.clone(), type_name)) {
+                    continue;
+                }
+                let counts = types.entry(path.clone()).or_insert_with(|| {
+                    paths.push(path);
+                    vec![]
+                });
+                match counts.iter_mut().find(|(name, _)| *name == type_name) {
+                    Some((_, n)) => *n += 1,
+                    None => counts.push((type_name, 1)),
+                }
+            }
+        }
+
+        let schema = paths
+            .into_iter()
+            .map(|path| {
+                let counts = &types[&path];
+                let mut best: Option<(&str, usize)> = None;
+                for &(name, n) in counts.iter().filter(|(name, _)| *name != "null") {
+                    if best.is_none_or(|(_, max)| n > max) {
+                        best = Some((name, n));
+                    }
+                }
+                let type_name = best.map_or("null", |(name, _)| name);
+                (path, type_name.to_string())
+            })
+            .collect();
+        Ok(schema)
+    }
+
+    /// Inserts `doc` and returns its `_id`, generated by the driver unless the
+    /// document already has one.
+    pub async fn insert_document(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+        doc: Document,
+    ) -> anyhow::Result<Bson> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            anyhow::bail!("Not connected to a MongoDB server");
+        };
+
+        let collection = client
+            .database(db_name)
+            .collection::<Document>(collection_name);
+        let id = doc.get("_id").cloned();
+        match collection.insert_one(doc).await {
+            Ok(result) => Ok(result.inserted_id),
+            Err(e) => match id {
+                Some(id) if is_duplicate_id(&e) => {
+                    anyhow::bail!("A document with _id {} already exists", id)
+                }
+                _ => Err(e.into()),
+            },
+        }
+    }
+
+    /// Applies an update document (`$set`, `$unset`, ...) to the first match
+    /// of `filter` and returns how many documents were modified.
+    pub async fn update_document(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+        filter: Document,
+        update: Document,
+    ) -> anyhow::Result<u64> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            anyhow::bail!("Not connected to a MongoDB server");
+        };
+
+        let collection = client
+            .database(db_name)
+            .collection::<Document>(collection_name);
+        let result = collection.update_one(filter, update).await?;
+        Ok(result.modified_count)
+    }
+
+    /// Applies `update` to every match of `filter` and returns how many
+    /// documents were modified.
+    pub async fn update_many(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+        filter: Document,
+        update: Document,
+    ) -> anyhow::Result<u64> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            anyhow::bail!("Not connected to a MongoDB server");
+        };
+
+        let collection = client
+            .database(db_name)
+            .collection::<Document>(collection_name);
+        let result = collection.update_many(filter, update).await?;
+        Ok(result.modified_count)
+    }
+
+    /// Replaces the first match of `filter` with `replacement` and returns how
+    /// many documents were modified.
+    pub async fn replace_document(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+        filter: Document,
+        replacement: Document,
+    ) -> anyhow::Result<u64> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            anyhow::bail!("Not connected to a MongoDB server");
+        };
+
+        let collection = client
+            .database(db_name)
+            .collection::<Document>(collection_name);
+        let result = collection.replace_one(filter, replacement).await?;
+        Ok(result.modified_count)
+    }
+
+    /// Deletes the first match of `filter` and returns how many documents
+    /// were deleted.
+    pub async fn delete_document(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+        filter: Document,
+    ) -> anyhow::Result<u64> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            anyhow::bail!("Not connected to a MongoDB server");
+        };
+
+        let collection = client
+            .database(db_name)
+            .collection::<Document>(collection_name);
+        let result = collection.delete_one(filter).await?;
+        Ok(result.deleted_count)
+    }
+
+    /// Drops a collection along with its documents and indexes.
+    pub async fn drop_collection(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+    ) -> anyhow::Result<()> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            anyhow::bail!("Not connected to a MongoDB server");
+        };
+
+        client
+            .database(db_name)
+            .collection::<Document>(collection_name)
+            .drop()
+            .await?;
+        Ok(())
+    }
+
+    /// Drops a database along with every collection in it.
+    pub async fn drop_database(&self, db_name: &str) -> anyhow::Result<()> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            anyhow::bail!("Not connected to a MongoDB server");
+        };
+
+        client.database(db_name).drop().await?;
+        Ok(())
+    }
+
+    /// Runs `collStats` on a collection. Views have no storage of their own, so
+    /// for them this returns an empty document instead of the server error.
+    pub async fn collection_stats(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+    ) -> anyhow::Result<Document> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            anyhow::bail!("Not connected to a MongoDB server");
+        };
+
+        let db = client.database(db_name);
+        match db.run_command(doc! { "collStats": collection_name }).await {
+            Ok(stats) => Ok(stats),
+            Err(e) if is_view_error(&e) => Ok(Document::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Explains a find with `executionStats` verbosity, so the reply holds
+    /// both the winning plan and what running it cost.
+    pub async fn explain_find(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+        filter: Document,
+        sort: Option<Document>,
+    ) -> anyhow::Result<Document> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            anyhow::bail!("Not connected to a MongoDB server");
+        };
+
+        let mut find = doc! { "find": collection_name, "filter": filter };
+        if let Some(sort) = sort {
+            find.insert("sort", sort);
+        }
+        let explain = client
+            .database(db_name)
+            .run_command(doc! { "explain": find, "verbosity": "executionStats" })
+            .await?;
+        Ok(explain)
+    }
+
+    /// Runs `dbStats` on a database. `totalSize` only exists from MongoDB 4.4;
+    /// older servers get storage plus index size.
+    pub async fn database_stats(&self, db_name: &str) -> anyhow::Result<DatabaseStats> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            anyhow::bail!("Not connected to a MongoDB server");
+        };
+
+        let stats = client
+            .database(db_name)
+            .run_command(doc! { "dbStats": 1 })
+            .await?;
+        let total_size = bson_u64(stats.get("totalSize")).or_else(|| {
+            Some(bson_u64(stats.get("storageSize"))? + bson_u64(stats.get("indexSize"))?)
+        });
+        Ok(DatabaseStats {
+            total_size,
+            collections: bson_u64(stats.get("collections")),
+        })
+    }
+
+    /// Returns the `listCollections` entry for a single collection, which holds
+    /// its type, creation options and info.
+    pub async fn collection_options(
+        &self,
+        db_name: &str,
+        collection_name: &str,
+    ) -> anyhow::Result<Document> {
+        let guard = self.client.lock().await;
+        let Some(client) = &*guard else {
+            return Ok(Document::new());
+        };
+
+        let db = client.database(db_name);
+        let reply = db
+            .run_command(doc! {
+                "listCollections": 1,
+                "filter": { "name": collection_name },
+            })
+            .await?;
+        let entry = reply
+            .get_document("cursor")?
+            .get_array("firstBatch")?
+            .first()
+            .and_then(|b| b.as_document())
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Collection '{}' not found", collection_name))?;
+        Ok(entry)
+    }
+}
+
+async fn list_collection_infos(db: &Database) -> anyhow::Result<Vec<CollectionInfo>> {
+    let specs: Vec<_> = db.list_collections().await?.try_collect().await?;
+    let collections = specs
+        .into_iter()
+        .map(|spec| CollectionInfo {
+            kind: Some(
+                match spec.collection_type {
+                    CollectionType::View => "view",
+                    CollectionType::Timeseries => "timeseries",
+                    _ => "collection",
+                }
+                .to_string(),
+            ),
+            capped: spec.options.capped.unwrap_or(false),
+            estimated_count: None,
+            name: spec.name,
+        })
+        .collect();
+    Ok(collections)
+}
+
+/// A non-negative number of any BSON numeric type; `dbStats` mixes them.
+fn bson_u64(value: Option<&Bson>) -> Option<u64> {
+    match value? {
+        Bson::Int32(n) => u64::try_from(*n).ok(),
+        Bson::Int64(n) => u64::try_from(*n).ok(),
+        Bson::Double(n) if *n >= 0.0 => Some(*n as u64),
+        _ => None,
+    }
+}
+
+/// URI options whose values are secrets.
+const SECRET_URI_OPTIONS: [&str; 2] = ["authMechanismProperties", "tlsCertificateKeyFilePassword"];
+
+/// `uri` with the password (and secret options) replaced by `***`, for
+/// display and logs. Works on `mongodb://` and `mongodb+srv://` alike; URIs
+/// without credentials come back unchanged. Doesn't validate the URI, so a
+/// malformed one is still masked as far as it can be split.
+pub fn redact_uri(uri: &str) -> String {
+    let Some((scheme, rest)) = uri.split_once("://") else {
+        return uri.to_string();
+    };
+    // Reserved characters in the password must be percent-encoded, so the
+    // first `/` or `?` ends the authority and its last `@` ends the userinfo
+    let authority_end = rest.find(['/', '?']).unwrap_or(rest.len());
+    let (authority, tail) = rest.split_at(authority_end);
+    let authority = match authority.rsplit_once('@') {
+        Some((userinfo, hosts)) => match userinfo.split_once(':') {
+            Some((user, _)) => format!("{}:***@{}", user, hosts),
+            None => authority.to_string(),
+        },
+        None => authority.to_string(),
+    };
+    let tail = match tail.split_once('?') {
+        Some((path, query)) => {
+            let options: Vec<String> = query
+                .split('&')
+                .map(|pair| match pair.split_once('=') {
+                    Some((key, _))
+                        if SECRET_URI_OPTIONS
+                            .iter()
+                            .any(|secret| secret.eq_ignore_ascii_case(key)) =>
+                    {
+                        format!("{}=***", key)
+                    }
+                    _ => pair.to_string(),
+                })
+                .collect();
+            format!("{}?{}", path, options.join("&"))
+        }
+        None => tail.to_string(),
+    };
+    format!("{}://{}{}", scheme, authority, tail)
+}
+
+/// `text` with every MongoDB URI in it passed through [`redact_uri`]. A URI
+/// runs until whitespace or a quote, which covers error messages and
+/// `Debug` output.
+pub fn redact_uris(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("mongodb") {
+        let (before, candidate) = rest.split_at(start);
+        out.push_str(before);
+        if !candidate.starts_with("mongodb://") && !candidate.starts_with("mongodb+srv://") {
+            out.push_str("mongodb");
+            rest = &candidate["mongodb".len()..];
+            continue;
+        }
+        let end = candidate
+            .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '`'))
+            .unwrap_or(candidate.len());
+        out.push_str(&redact_uri(&candidate[..end]));
+        rest = &candidate[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Short type name of a value, using the `$type` aliases.
+pub fn bson_type_name(value: &Bson) -> &'static str {
+    match value {
+        Bson::Double(_) => "double",
+        Bson::String(_) => "string",
+        Bson::Document(_) => "object",
+        Bson::Array(_) => "array",
+        Bson::Binary(_) => "binData",
+        Bson::ObjectId(_) => "objectId",
+        Bson::Boolean(_) => "bool",
+        Bson::DateTime(_) => "date",
+        Bson::Null => "null",
+        Bson::RegularExpression(_) => "regex",
+        Bson::Int32(_) => "int",
+        Bson::Timestamp(_) => "timestamp",
+        Bson::Int64(_) => "long",
+        Bson::Decimal128(_) => "decimal",
+        _ => "other",
+    }
+}
+
+/// Appends the dotted path and type of every field in `doc`, descending into
+/// sub-documents and into documents held in arrays.
+fn collect_paths(prefix: &str, doc: &Document, out: &mut Vec<(String, &'static str)>) {
+    for (key, value) in doc {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        out.push((path.clone(), bson_type_name(value)));
+        match value {
+            Bson::Document(sub) => collect_paths(&path, sub, out),
+            Bson::Array(items) => {
+                for sub in items.iter().filter_map(Bson::as_document) {
+                    collect_paths(&path, sub, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Whether a driver error means the credentials lack the needed privileges.
+fn is_auth_error(e: &mongodb::error::Error) -> bool {
+    match e.kind.as_ref() {
+        ErrorKind::Authentication { .. } => true,
+        // 13 = Unauthorized
+        ErrorKind::Command(err) => err.code == 13,
+        _ => false,
+    }
+}
+
+/// Whether a change stream was refused because the server is a standalone.
+fn is_not_replica_set(e: &mongodb::error::Error) -> bool {
+    match e.kind.as_ref() {
+        // 40573 = $changeStream is only supported on replica sets
+        ErrorKind::Command(err) => err.code == 40573,
+        _ => false,
+    }
+}
+
+/// Whether a command was rejected because the namespace is a view.
+fn is_view_error(e: &mongodb::error::Error) -> bool {
+    match e.kind.as_ref() {
+        // 166 = CommandNotSupportedOnView
+        ErrorKind::Command(err) => err.code == 166,
+        _ => false,
+    }
+}
+
+/// Whether a write failed because another document already has the same `_id`.
+fn is_duplicate_id(e: &mongodb::error::Error) -> bool {
+    match e.kind.as_ref() {
+        // 11000 = DuplicateKey; other unique indexes keep the server message
+        ErrorKind::Write(WriteFailure::WriteError(err)) => {
+            err.code == 11000 && err.message.contains("index: _id_")
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uri_default_database_from_path() {
+        assert_eq!(
+            MongoCore::uri_default_database("mongodb://localhost:27017/shop"),
+            Some("shop".to_string())
+        );
+        assert_eq!(
+            MongoCore::uri_default_database("mongodb://localhost:27017/shop?retryWrites=true"),
+            Some("shop".to_string())
+        );
+        assert_eq!(
+            MongoCore::uri_default_database("mongodb+srv://user:pw@cluster0.example.net/shop"),
+            Some("shop".to_string())
+        );
+    }
+
+    #[test]
+    fn uri_default_database_without_path() {
+        assert_eq!(
+            MongoCore::uri_default_database("mongodb://localhost:27017"),
+            None
+        );
+        assert_eq!(
+            MongoCore::uri_default_database("mongodb://localhost:27017/"),
+            None
+        );
+        assert_eq!(
+            MongoCore::uri_default_database("mongodb://localhost:27017/?authSource=admin"),
+            None
+        );
+        assert_eq!(
+            MongoCore::uri_default_database("mongodb+srv://cluster0.example.net"),
+            None
+        );
+        assert_eq!(MongoCore::uri_default_database("not a uri"), None);
     }
 }
@@ -11,31 +11,134 @@ pub enum Action {
     Quit,
     ClearScreen,
     Error(String),
+    // A background task failed: ends its own spinner, then shows the error
+    TaskFailed(crate::components::mongo_viewer::defs::LoadingKind, String),
     Help,
+    EditConfig,
 
     // MongoDB Actions
     Connect(String),
+    OpenNamespace(String, Option<String>), // DB, Collection: opened once the databases load
     SelectDatabase(usize),
     SelectCollection(usize),
     RefreshDatabases,
     RefreshDocuments,
+    SampleDocuments(String, String), // DB, Collection
     NextPage,
     PreviousPage,
     ToggleViewMode,
-    OpenJsonPopup(String, String), // Json, Title
+    OpenJsonPopup(String, String),                        // Json, Title
+    OpenDocumentTree(mongo_core::bson::Document, String), // Document, Title
     OpenConnectionManager,
+    OpenConnectionEditor(usize),
     OpenQueryBuilder,
-    OpenFieldSelector(Vec<String>, Vec<String>), // All fields, Visible fields
+    OpenQueryHistory,
+    OpenSavedQueries,
+    SaveQuery(String),        // Name
+    DeleteSavedQuery(String), // Name
+    OpenAggregationBuilder,
+    RunAggregation(Vec<mongo_core::bson::Document>), // Pipeline stages
+    OpenFieldSelector(Vec<String>, Vec<String>),     // All fields, Visible fields
     ClosePopup,
     UpdateVisibleFields(Vec<String>),
+    LoadCollectionOptions(String, String), // DB, Collection
+    LoadCollectionStats(String, String),   // DB, Collection
+    LoadCollectionMetadata(String),        // DB
+    LoadDatabaseStats(String),             // DB
+    OpenCommandRunner,
+    OpenJumpToRow,
+    JumpToRow(usize), // 0-based row in the current page
+    OpenGoToPage,
+    OpenExport(Vec<String>), // Columns visible in the table, for CSV
+    ExportDocuments(
+        std::path::PathBuf,
+        crate::components::mongo_viewer::export::ExportFormat,
+    ),
+    GoToPage(usize),                                // 0-based page
+    RunCommand(String, mongo_core::bson::Document), // DB, Command
+    LoadServerLog,
+    ShowLogs,             // Tail this app's own log file
+    LoadDistinct(String), // Field
+    LoadIndexes,
+    ExplainQuery, // Explain the current filter and sort
+    StartWatch,   // Tail the selected collection's change stream
+    StopWatch,
+    NextTab,
+    PreviousTab,
+    CloseTab,
+    OpenInsertDocument,
+    InsertDocument(String),    // Document JSON
+    OpenDocumentEditor(usize), // Row in the current page
+    // Filter, then a replacement or an update document (keys starting with `$`)
+    SaveDocumentEdit(mongo_core::bson::Document, mongo_core::bson::Document),
+    RequestDeleteDocument(usize), // Row in the current page
+    DeleteDocument(mongo_core::bson::Document), // Filter
+    OpenBulkUpdate,
+    // DB, Collection, filter and update document; counts the matches before
+    // asking to apply
+    CountBulkUpdate(
+        String,
+        String,
+        mongo_core::bson::Document,
+        mongo_core::bson::Document,
+    ),
+    // DB, Collection, filter, update
+    UpdateMany(
+        String,
+        String,
+        mongo_core::bson::Document,
+        mongo_core::bson::Document,
+    ),
+    RequestDrop(String, Option<String>), // DB, Collection (whole DB if None)
+    DropCollection(String, String),      // DB, Collection
+    DropDatabase(String),                // DB
 
     // Connection Actions
-    SaveConnection(String, String), // Name, URI
+    SaveConnection(crate::config::Connection),
+    UpdateConnection(usize, crate::config::Connection),
+    TestConnection(String, mongo_core::ConnectOptions), // URI, overrides
     DeleteConnection(usize),
 
     // Async Results
     DatabasesLoaded(Vec<mongo_core::DatabaseInfo>),
-    DocumentsLoaded(Vec<mongo_core::bson::Document>, u64),
-    SchemaLoaded(Vec<String>),
+    CollectionMetadataLoaded(String, Vec<mongo_core::CollectionInfo>),
+    DatabaseStatsLoaded(String, mongo_core::DatabaseStats), // DB, sizes
+    // Docs, total, has next page, query id
+    DocumentsLoaded(
+        Vec<mongo_core::bson::Document>,
+        mongo_core::DocumentCount,
+        bool,
+        u64,
+    ),
+    QueryFailed(String, u64),            // Error, query id
+    SchemaLoaded(Vec<(String, String)>), // Dotted path, inferred type
+    SchemaFailed(String),
+    DocumentInserted(String), // Formatted _id
+    DocumentSaved(u64),       // Modified count
+    DocumentDeleted(u64),     // Deleted count
+    // DB, Collection, filter, update, matching documents
+    BulkUpdateCounted(
+        String,
+        String,
+        mongo_core::bson::Document,
+        mongo_core::bson::Document,
+        u64,
+    ),
+    BulkUpdateCountFailed(String),
+    DocumentsUpdated(u64),                   // Modified count
+    Dropped(String, Option<String>),         // DB, Collection
+    ConnectionTested(Result<u64, String>),   // Latency in ms or error
+    ConnectionStatus(bool),                  // Whether the server answered the last ping
+    ExportProgress(u64, Option<u64>),        // Written, expected total
+    ExportFinished(std::path::PathBuf, u64), // Path, documents written
+    ExportCancelled,
+    ExportFailed(String),
+    ChangeEvent(mongo_core::bson::Document), // Change stream event
+    WatchStopped,
+    DistinctLoaded(String, Vec<mongo_core::bson::Bson>), // Field, values
+    IndexesLoaded(Vec<mongo_core::bson::Document>),
+    ExplainLoaded(mongo_core::bson::Document),
+    CollectionStatsLoaded(String, mongo_core::bson::Document), // Namespace, collStats reply
     ErrorMsg(String),
+    ForTab(usize, Box<Action>), // Tab id, result of one of its tasks
 }
@@ -1,3 +1,5 @@
+use std::{env, process::Command};
+
 use crossterm::event::KeyEvent;
 use ratatui::prelude::Rect;
 use serde::{Deserialize, Serialize};
@@ -6,8 +8,9 @@ use tracing::debug;
 
 use crate::{
     action::Action,
+    cli::StartupTarget,
     components::{mongo_viewer::MongoViewer, Component},
-    config::Config,
+    config::{self, Config},
     tui::{Event, Tui},
 };
 
@@ -15,6 +18,8 @@ pub struct App {
     config: Config,
     tick_rate: f64,
     frame_rate: f64,
+    no_color: bool,
+    startup: Option<StartupTarget>,
     components: Vec<Box<dyn Component>>,
     should_quit: bool,
     should_suspend: bool,
@@ -31,15 +36,24 @@ pub enum Mode {
 }
 
 impl App {
-    pub fn new(tick_rate: f64, frame_rate: f64) -> color_eyre::Result<Self> {
+    pub fn new(
+        tick_rate: f64,
+        frame_rate: f64,
+        no_color: bool,
+        startup: Option<StartupTarget>,
+    ) -> color_eyre::Result<Self> {
         let (action_tx, action_rx) = mpsc::unbounded_channel();
+        let mut config = Config::new()?;
+        config.config.no_color = no_color;
         Ok(Self {
             tick_rate,
             frame_rate,
+            no_color,
+            startup,
             components: vec![Box::new(MongoViewer::new())],
             should_quit: false,
             should_suspend: false,
-            config: Config::new()?,
+            config,
             _mode: Mode::Home,
             last_tick_key_events: Vec::new(),
             action_tx,
@@ -50,7 +64,8 @@ impl App {
     pub async fn run(&mut self) -> color_eyre::Result<()> {
         let mut tui = Tui::new()?
             .tick_rate(self.tick_rate)
-            .frame_rate(self.frame_rate);
+            .frame_rate(self.frame_rate)
+            .mouse(self.config.config.mouse);
         tui.enter()?;
 
         for component in self.components.iter_mut() {
@@ -64,6 +79,10 @@ impl App {
             component.init(Rect::new(0, 0, size.width, size.height))?;
         }
 
+        if let Some(target) = self.startup.take() {
+            self.dispatch_startup(target)?;
+        }
+
         let action_tx = self.action_tx.clone();
         loop {
             self.handle_events(&mut tui).await?;
@@ -82,6 +101,24 @@ impl App {
         Ok(())
     }
 
+    /// Connects to the `--uri` target, a saved connection's name or a raw
+    /// URI, and asks for its `--db`/`--collection` once databases load.
+    fn dispatch_startup(&mut self, target: StartupTarget) -> color_eyre::Result<()> {
+        let uri = self
+            .config
+            .config
+            .connections
+            .iter()
+            .find(|c| c.name == target.uri)
+            .map_or(target.uri, |c| c.uri.clone());
+        self.action_tx.send(Action::Connect(uri))?;
+        if let Some(db) = target.db {
+            self.action_tx
+                .send(Action::OpenNamespace(db, target.collection))?;
+        }
+        Ok(())
+    }
+
     async fn handle_events(&mut self, tui: &mut Tui) -> color_eyre::Result<()> {
         let Some(event) = tui.next_event().await else {
             return Ok(());
@@ -112,7 +149,8 @@ impl App {
     fn handle_actions(&mut self, tui: &mut Tui) -> color_eyre::Result<()> {
         while let Ok(action) = self.action_rx.try_recv() {
             if action != Action::Tick && action != Action::Render {
-                debug!("{action:?}");
+                // Connect and connection actions carry URIs with passwords
+                debug!("{}", mongo_core::redact_uris(&format!("{action:?}")));
             }
             match action {
                 Action::Tick => {
@@ -124,19 +162,7 @@ impl App {
                 Action::ClearScreen => tui.terminal.clear()?,
                 Action::Resize(w, h) => self.handle_resize(tui, w, h)?,
                 Action::Render => self.render(tui)?,
-                Action::SaveConnection(ref name, ref uri) => {
-                    self.config
-                        .config
-                        .connections
-                        .push(crate::config::Connection {
-                            name: name.clone(),
-                            uri: uri.clone(),
-                        });
-                    if let Err(e) = self.config.save() {
-                        self.action_tx
-                            .send(Action::Error(format!("Failed to save config: {}", e)))?;
-                    }
-                }
+                Action::EditConfig => self.edit_config(tui)?,
                 _ => {}
             }
             for component in self.components.iter_mut() {
@@ -148,6 +174,46 @@ impl App {
         Ok(())
     }
 
+    /// Opens the config file in `$EDITOR` and reloads it once the editor exits.
+    /// Without `$EDITOR` this is a no-op; the viewer copies the path instead.
+    fn edit_config(&mut self, tui: &mut Tui) -> color_eyre::Result<()> {
+        let Some(editor) = env::var("EDITOR").ok().filter(|e| !e.trim().is_empty()) else {
+            return Ok(());
+        };
+        let mut parts = editor.split_whitespace();
+        let Some(program) = parts.next() else {
+            return Ok(());
+        };
+
+        tui.exit()?;
+        let status = Command::new(program)
+            .args(parts)
+            .arg(config::config_file())
+            .status();
+        tui.enter()?;
+        tui.terminal.clear()?;
+
+        if let Err(e) = status {
+            self.action_tx
+                .send(Action::Error(format!("Failed to run {}: {}", editor, e)))?;
+            return Ok(());
+        }
+        match Config::new() {
+            Ok(mut config) => {
+                config.config.no_color = self.no_color;
+                self.config = config;
+                for component in self.components.iter_mut() {
+                    component.register_config_handler(self.config.clone())?;
+                }
+            }
+            Err(e) => {
+                self.action_tx
+                    .send(Action::Error(format!("Failed to reload config: {}", e)))?;
+            }
+        }
+        Ok(())
+    }
+
     fn handle_resize(&mut self, tui: &mut Tui, w: u16, h: u16) -> color_eyre::Result<()> {
         tui.resize(Rect::new(0, 0, w, h))?;
         self.render(tui)?;
@@ -20,4 +20,51 @@ pub struct Cli {
         default_value_t = 60.0
     )]
     pub frame_rate: f64,
+
+    #[arg(
+        long,
+        help = "Render without colors (also enabled by the NO_COLOR env var)"
+    )]
+    pub no_color: bool,
+
+    #[arg(
+        long,
+        value_name = "URI|NAME",
+        help = "Connect on startup, to a URI or a saved connection's name"
+    )]
+    pub uri: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        requires = "uri",
+        help = "Database to open after connecting"
+    )]
+    pub db: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        requires = "db",
+        help = "Collection to open after connecting (needs --db)"
+    )]
+    pub collection: Option<String>,
+}
+
+/// Where to go right after launch, from `--uri`, `--db` and `--collection`.
+#[derive(Debug, Clone)]
+pub struct StartupTarget {
+    pub uri: String,
+    pub db: Option<String>,
+    pub collection: Option<String>,
+}
+
+impl Cli {
+    pub fn startup_target(&self) -> Option<StartupTarget> {
+        Some(StartupTarget {
+            uri: self.uri.clone()?,
+            db: self.db.clone(),
+            collection: self.collection.clone(),
+        })
+    }
 }
@@ -1,67 +1,194 @@
 use crate::action::Action;
-use crate::config::Connection;
+use crate::config::{self, ClipboardSetting, Config, Connection};
 use arboard::Clipboard;
+use base64::{engine::general_purpose::STANDARD, Engine};
 use mongo_core::bson::Document;
-use mongo_core::{DatabaseInfo, MongoCore};
+use mongo_core::{bson_type_name, DatabaseInfo, FindOptions, MongoCore};
 use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc::UnboundedSender;
+use tokio::task::AbortHandle;
 
-use super::defs::PaginationState;
+use super::defs::{LoadingKind, PaginationState, QueryField, QueryState};
+use super::parse_optional_document;
+use super::theme::Theme;
+use super::util::{check_projection, check_sort, parse_extended_json};
 use tui_textarea::TextArea;
 
 pub struct MongoContext {
     pub action_tx: Option<UnboundedSender<Action>>,
     pub mongo_core: MongoCore,
+    pub config: Config,
 
     // Data
     pub connections: Vec<Connection>,
     pub databases: Vec<DatabaseInfo>,
+    /// Set once the database list has been fetched for the current connection.
+    pub databases_loaded: bool,
     pub documents: Vec<Document>,
+    /// Dotted field paths of the selected collection and their most common
+    /// type, from `infer_schema`.
+    pub schema: Vec<(String, String)>,
+    /// Collection the schema was last requested for.
+    pub schema_namespace: Option<(String, String)>,
     pub pagination: PaginationState,
+    /// Row selected in the documents pane.
+    pub selected_document: Option<usize>,
+    /// Whether the selected document is shown beside the table.
+    pub show_detail_drawer: bool,
 
     // Selection Context
     pub selected_connection: Option<usize>,
     pub selected_db_index: Option<usize>,
     pub selected_coll_index: Option<usize>,
+    /// Database and collection to open once the databases load, from the
+    /// command line.
+    pub startup_namespace: Option<(String, Option<String>)>,
+    /// Database from the connection URI path; `None` when the URI has none.
+    pub default_database: Option<String>,
+    /// URI of the last `Connect`, reused by reconnect.
+    pub connected_uri: Option<String>,
+    /// Outcome of the latest connect or ping; `None` before the first one.
+    pub connection_alive: Option<bool>,
 
     // Query Inputs
     pub query_input: TextArea<'static>,
     pub projection_input: TextArea<'static>,
     pub sort_input: TextArea<'static>,
     pub limit_input: TextArea<'static>,
-    pub input_validation_errors: HashMap<crate::components::mongo_viewer::defs::QueryField, String>,
+    pub pipeline_input: TextArea<'static>,
+    pub input_validation_errors: HashMap<QueryField, String>,
+    pub query_memory: HashMap<(String, String), QueryState>,
+    /// Queries that loaded documents this session, oldest first.
+    pub query_history: Vec<QueryState>,
+    /// Set while auto-refresh is on: when the query last re-ran (or when
+    /// auto-refresh was turned on).
+    pub auto_refresh: Option<Instant>,
+    /// Collection whose change stream is being tailed.
+    pub watching: Option<(String, String)>,
+    /// Task fetching documents, aborted when cancelled or replaced.
+    pub query_task: Option<AbortHandle>,
+    /// Bumped by every query; results tagged with an older one are stale.
+    pub query_id: u64,
+    /// The running query was started by auto-refresh, so its results leave
+    /// the focus where the user put it.
+    pub query_in_background: bool,
 
     // System
-    pub clipboard: Option<Clipboard>,
+    /// Server work in flight, each with the frame counter of its spinner.
+    pub loading: HashMap<LoadingKind, usize>,
+    pub clipboard: ClipboardBackend,
+    pub status: Option<(String, Instant)>,
+    pub theme: Theme,
+}
+
+/// Where copies go. The system clipboard is missing over SSH and on headless
+/// machines; OSC 52 then asks the terminal to set its own clipboard, and the
+/// file is there for terminals that ignore it.
+pub enum ClipboardBackend {
+    System(Clipboard),
+    Osc52,
+    File(PathBuf),
+}
+
+impl ClipboardBackend {
+    pub fn new(setting: ClipboardSetting) -> Self {
+        match setting {
+            ClipboardSetting::Auto => Clipboard::new().map_or(Self::Osc52, Self::System),
+            ClipboardSetting::Osc52 => Self::Osc52,
+            ClipboardSetting::File => Self::File(config::get_data_dir().join("clipboard.txt")),
+        }
+    }
+
+    /// Copies `text`, returning a note on where it went for the status
+    /// message (empty for the system clipboard).
+    pub fn set_text(&mut self, text: String) -> Result<String, String> {
+        match self {
+            Self::System(cb) => cb
+                .set_text(text)
+                .map(|_| String::new())
+                .map_err(|e| e.to_string()),
+            Self::Osc52 => {
+                // Written to stderr, where the TUI draws
+                let mut out = std::io::stderr();
+                write!(out, "\x1b]52;c;{}\x07", STANDARD.encode(text))
+                    .and_then(|_| out.flush())
+                    .map(|_| " (OSC 52)".to_string())
+                    .map_err(|e| e.to_string())
+            }
+            Self::File(path) => path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&path, text))
+                .map(|_| format!(" to {}", path.display()))
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// How long a status message stays in the footer.
+const STATUS_TTL: Duration = Duration::from_secs(2);
+
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Oldest queries are dropped past this many.
+const QUERY_HISTORY_LIMIT: usize = 50;
+
+fn query_textarea(placeholder: &str) -> TextArea<'static> {
+    query_textarea_with(placeholder, "")
+}
+
+fn query_textarea_with(placeholder: &str, text: &str) -> TextArea<'static> {
+    let mut input = if text.is_empty() {
+        TextArea::default()
+    } else {
+        TextArea::from(text.lines())
+    };
+    input.set_placeholder_text(placeholder);
+    input
 }
 
 impl Default for MongoContext {
     fn default() -> Self {
-        let mut query = TextArea::default();
-        query.set_placeholder_text("{}");
-        let mut proj = TextArea::default();
-        proj.set_placeholder_text("{}");
-        let mut sort = TextArea::default();
-        sort.set_placeholder_text("{}");
-        let mut limit = TextArea::default();
-        limit.set_placeholder_text("10");
-
         Self {
             action_tx: None,
             mongo_core: MongoCore::new(),
+            config: Config::default(),
             connections: vec![],
             databases: vec![],
+            databases_loaded: false,
             documents: vec![],
+            schema: vec![],
+            schema_namespace: None,
             pagination: PaginationState::default(),
+            selected_document: None,
+            show_detail_drawer: false,
             selected_connection: None,
             selected_db_index: None,
             selected_coll_index: None,
-            query_input: query,
-            projection_input: proj,
-            sort_input: sort,
-            limit_input: limit,
+            startup_namespace: None,
+            default_database: None,
+            connected_uri: None,
+            connection_alive: None,
+            query_input: query_textarea("{}"),
+            projection_input: query_textarea("{}"),
+            sort_input: query_textarea("{}"),
+            limit_input: query_textarea("page size"),
+            pipeline_input: query_textarea(r#"[{ "$match": {} }]"#),
             input_validation_errors: HashMap::new(),
-            clipboard: Clipboard::new().ok(),
+            query_memory: HashMap::new(),
+            query_history: vec![],
+            auto_refresh: None,
+            watching: None,
+            query_task: None,
+            query_id: 0,
+            query_in_background: false,
+            loading: HashMap::new(),
+            clipboard: ClipboardBackend::new(ClipboardSetting::Auto),
+            status: None,
+            theme: Theme::default(),
         }
     }
 }
@@ -70,4 +197,315 @@ impl MongoContext {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Shows a transient message in the global footer.
+    pub fn set_status(&mut self, msg: impl Into<String>) {
+        self.status = Some((msg.into(), Instant::now()));
+    }
+
+    pub fn start_loading(&mut self, kind: LoadingKind) {
+        self.loading.entry(kind).or_insert(0);
+    }
+
+    pub fn finish_loading(&mut self, kind: LoadingKind) {
+        self.loading.remove(&kind);
+    }
+
+    pub fn is_loading(&self, kind: LoadingKind) -> bool {
+        self.loading.contains_key(&kind)
+    }
+
+    /// Aborts the running query, if any, and returns the id the next
+    /// `DocumentsLoaded` must carry to be applied.
+    pub fn begin_query(&mut self) -> u64 {
+        if let Some(task) = self.query_task.take() {
+            task.abort();
+        }
+        self.query_id += 1;
+        self.query_in_background = false;
+        self.start_loading(LoadingKind::Documents);
+        self.query_id
+    }
+
+    /// Stops the running query. Bumping the id also drops a result the task
+    /// had already sent before it was aborted.
+    pub fn cancel_query(&mut self) {
+        if let Some(task) = self.query_task.take() {
+            task.abort();
+        }
+        self.query_id += 1;
+        self.finish_loading(LoadingKind::Documents);
+    }
+
+    /// Advances every running spinner; called on each tick.
+    pub fn tick_loading(&mut self) {
+        for frame in self.loading.values_mut() {
+            *frame = frame.wrapping_add(1);
+        }
+    }
+
+    /// The spinner frame to draw for `kind`, `None` when it isn't loading.
+    pub fn spinner(&self, kind: LoadingKind) -> Option<&'static str> {
+        self.loading
+            .get(&kind)
+            .map(|frame| SPINNER[frame / 5 % SPINNER.len()])
+    }
+
+    /// Copies `text` and reports it in the footer as "Copied `what`", or
+    /// says why it could not. Returns whether it worked.
+    pub fn copy(&mut self, text: String, what: &str) -> bool {
+        match self.clipboard.set_text(text) {
+            Ok(note) => {
+                self.set_status(format!("Copied {}{}", what, note));
+                true
+            }
+            Err(e) => {
+                self.set_status(format!("Copy failed: {}", e));
+                false
+            }
+        }
+    }
+
+    /// Drops the status message once it has been visible long enough.
+    pub fn expire_status(&mut self) {
+        if let Some((_, since)) = &self.status {
+            if since.elapsed() >= STATUS_TTL {
+                self.status = None;
+            }
+        }
+    }
+
+    /// The limit typed in the query builder; `None` when blank or invalid.
+    pub fn query_limit(&self) -> Option<usize> {
+        self.limit_input
+            .lines()
+            .join("")
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|limit| *limit > 0)
+    }
+
+    /// Documents shown on a page: the page size, cut down by a smaller limit.
+    pub fn rows_per_page(&self) -> usize {
+        let page_size = self.pagination.page_size.max(1);
+        self.query_limit()
+            .map_or(page_size, |limit| limit.min(page_size))
+    }
+
+    /// Number of pages at the current page size; `None` while the count loads.
+    pub fn page_count(&self) -> Option<usize> {
+        let page_size = self.pagination.page_size.max(1);
+        let total = self.pagination.total_count? as usize;
+        Some(total.div_ceil(page_size).max(1))
+    }
+
+    /// Changes the page size by `delta`, clamped to `1..=max_documents`.
+    /// The page is recomputed so the first document on screen stays in view.
+    pub fn adjust_page_size(&mut self, delta: isize) {
+        let old = self.pagination.page_size.max(1);
+        let max = self.config.config.max_documents.max(1);
+        let new = old.saturating_add_signed(delta).clamp(1, max);
+        self.pagination.page_size = new;
+        self.pagination.current_page = self.pagination.current_page * old / new;
+        self.set_status(format!("Page size: {}", new));
+    }
+
+    /// The find options the query inputs and current page translate to.
+    /// Blank or invalid JSON inputs are left out.
+    pub fn build_find_options(&self) -> FindOptions {
+        FindOptions {
+            filter: parse_optional_document(&self.query_input.lines().join("\n")),
+            projection: parse_optional_document(&self.projection_input.lines().join("\n")),
+            sort: parse_optional_document(&self.sort_input.lines().join("\n")),
+            limit: Some(self.rows_per_page() as i64),
+            skip: Some((self.pagination.current_page * self.pagination.page_size.max(1)) as u64),
+        }
+    }
+
+    /// Checks the query inputs, recording a message in
+    /// `input_validation_errors` for each one that does not parse, for sort
+    /// directions other than 1/-1, projection values other than 0/1/booleans
+    /// and limits that are not a positive number. Blank inputs are valid.
+    pub fn validate_query_inputs(&mut self) -> bool {
+        // The pipeline is checked on its own by the aggregation builder
+        self.input_validation_errors
+            .retain(|field, _| *field == QueryField::Pipeline);
+        let inputs = [
+            (QueryField::Filter, &self.query_input),
+            (QueryField::Sort, &self.sort_input),
+            (QueryField::Projection, &self.projection_input),
+        ];
+        for (field, input) in inputs {
+            let text = input.lines().join("\n");
+            if text.trim().is_empty() {
+                continue;
+            }
+            let checked = parse_extended_json(&text).and_then(|doc| match field {
+                QueryField::Sort => check_sort(&doc),
+                QueryField::Projection => check_projection(&doc),
+                _ => Ok(()),
+            });
+            if let Err(e) = checked {
+                self.input_validation_errors.insert(field, e.to_string());
+            }
+        }
+        let limit = self.limit_input.lines().join("");
+        if !limit.trim().is_empty() && self.query_limit().is_none() {
+            self.input_validation_errors.insert(
+                QueryField::Limit,
+                "Limit must be a positive number".to_string(),
+            );
+        }
+        self.first_invalid_field().is_none()
+    }
+
+    /// The first query input with a validation error, in builder order.
+    pub fn first_invalid_field(&self) -> Option<QueryField> {
+        [
+            QueryField::Filter,
+            QueryField::Sort,
+            QueryField::Projection,
+            QueryField::Limit,
+        ]
+        .into_iter()
+        .find(|field| self.input_validation_errors.contains_key(field))
+    }
+
+    /// Top-level field names seen in the loaded documents, sorted.
+    pub fn loaded_fields(&self) -> Vec<String> {
+        let mut fields: Vec<String> = self
+            .documents
+            .iter()
+            .flat_map(|doc| doc.keys().cloned())
+            .collect();
+        fields.sort();
+        fields.dedup();
+        fields
+    }
+
+    /// Fields offered by the guided filter, with their type: the inferred
+    /// schema, else the loaded documents' top-level keys typed by the first
+    /// value seen.
+    pub fn filter_fields(&self) -> Vec<(String, String)> {
+        if !self.schema.is_empty() {
+            return self.schema.clone();
+        }
+        self.loaded_fields()
+            .into_iter()
+            .map(|field| {
+                let type_name = self
+                    .documents
+                    .iter()
+                    .find_map(|doc| doc.get(&field))
+                    .map_or("null", bson_type_name);
+                (field, type_name.to_string())
+            })
+            .collect()
+    }
+
+    /// Database to run database-level operations against: the selected one,
+    /// else the URI's default, else `admin`.
+    pub fn current_database(&self) -> String {
+        self.selected_namespace()
+            .map(|(db, _)| db.to_string())
+            .or_else(|| self.default_database.clone())
+            .unwrap_or_else(|| "admin".to_string())
+    }
+
+    /// Names of the currently selected database and collection.
+    pub fn selected_namespace(&self) -> Option<(&str, &str)> {
+        let db = self.databases.get(self.selected_db_index?)?;
+        let coll = db.collections.get(self.selected_coll_index?)?;
+        Some((db.name.as_str(), coll.name.as_str()))
+    }
+
+    /// Selects a collection by name. Returns false if it is not in the tree.
+    pub fn select_collection_by_name(&mut self, db_name: &str, coll_name: &str) -> bool {
+        let Some(db_idx) = self.databases.iter().position(|d| d.name == db_name) else {
+            return false;
+        };
+        let Some(coll_idx) = self.databases[db_idx]
+            .collections
+            .iter()
+            .position(|c| c.name == coll_name)
+        else {
+            return false;
+        };
+        self.select_collection(db_idx, coll_idx);
+        true
+    }
+
+    pub fn query_state(&self) -> QueryState {
+        QueryState {
+            filter: self.query_input.lines().join("\n"),
+            sort: self.sort_input.lines().join("\n"),
+            projection: self.projection_input.lines().join("\n"),
+            limit: self.limit_input.lines().join(""),
+        }
+    }
+
+    /// Selects a collection, stashing the query typed for the previous one and
+    /// restoring whatever was last used on the new one (or a fresh query).
+    pub fn select_collection(&mut self, db_idx: usize, coll_idx: usize) {
+        if let Some((db, coll)) = self.selected_namespace() {
+            let key = (db.to_string(), coll.to_string());
+            let state = self.query_state();
+            self.query_memory.insert(key, state);
+        }
+
+        self.selected_db_index = Some(db_idx);
+        self.selected_coll_index = Some(coll_idx);
+        self.reset_query();
+
+        let restored = self
+            .selected_namespace()
+            .and_then(|(db, coll)| self.query_memory.get(&(db.to_string(), coll.to_string())))
+            .cloned();
+        if let Some(state) = restored {
+            self.load_query(&state);
+        }
+    }
+
+    /// Fills the four query inputs from `state`.
+    pub fn load_query(&mut self, state: &QueryState) {
+        self.query_input = query_textarea_with("{}", &state.filter);
+        self.sort_input = query_textarea_with("{}", &state.sort);
+        self.projection_input = query_textarea_with("{}", &state.projection);
+        self.limit_input = query_textarea_with("page size", &state.limit);
+        self.input_validation_errors.clear();
+    }
+
+    /// Adds the current query to the history. Blank queries and repeats of
+    /// the latest entry (paging, refreshes) are not recorded.
+    pub fn record_query(&mut self) {
+        let state = self.query_state();
+        if state == QueryState::default() || self.query_history.last() == Some(&state) {
+            return;
+        }
+        self.query_history.push(state);
+        if self.query_history.len() > QUERY_HISTORY_LIMIT {
+            self.query_history.remove(0);
+        }
+    }
+
+    /// Replaces the filter input text.
+    pub fn set_filter(&mut self, filter: &str) {
+        self.query_input = query_textarea_with("{}", filter);
+    }
+
+    /// Replaces the sort input text.
+    pub fn set_sort(&mut self, sort: &str) {
+        self.sort_input = query_textarea_with("{}", sort);
+    }
+
+    /// Clears filter/sort/projection/limit and goes back to the first page.
+    pub fn reset_query(&mut self) {
+        self.query_input = query_textarea("{}");
+        self.projection_input = query_textarea("{}");
+        self.sort_input = query_textarea("{}");
+        self.limit_input = query_textarea("page size");
+        self.input_validation_errors.clear();
+        self.pagination.current_page = 0;
+    }
 }
@@ -1,13 +1,53 @@
+use mongo_core::bson::{Bson, Document};
 use ratatui::widgets::{ListState, TableState};
+use serde::{Deserialize, Serialize};
 // use std::collections::HashMap;
 use tui_textarea::TextArea;
 
+use super::json_tree::JsonTree;
+use crate::{action::Action, config::NamedQuery};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum QueryField {
     Filter,
     Sort,
     Limit,
     Projection,
+    /// Aggregation pipeline, edited in its own popup.
+    Pipeline,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionField {
+    Name,
+    Uri,
+    PoolSize,
+    AuthSource,
+    AppName,
+    Tls,
+}
+
+impl ConnectionField {
+    pub fn next(self) -> Self {
+        match self {
+            ConnectionField::Name => ConnectionField::Uri,
+            ConnectionField::Uri => ConnectionField::PoolSize,
+            ConnectionField::PoolSize => ConnectionField::AuthSource,
+            ConnectionField::AuthSource => ConnectionField::AppName,
+            ConnectionField::AppName => ConnectionField::Tls,
+            ConnectionField::Tls => ConnectionField::Name,
+        }
+    }
+}
+
+/// Server work that can be in flight. Each kind has its spinner in the pane
+/// waiting on it; `Other` covers popups and commands and spins in the footer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LoadingKind {
+    Connecting,
+    Databases,
+    Documents,
+    Other,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -16,10 +56,163 @@ pub enum ViewMode {
     Json,
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct PaginationState {
     pub current_page: usize,
+    /// Documents per page; pages start at `current_page * page_size`
+    /// whatever limit the query sets.
+    pub page_size: usize,
     pub total_count: Option<u64>,
+    /// Whether `total_count` is the collection's estimate rather than an
+    /// exact count of the query.
+    pub count_estimated: bool,
+    /// Whether the last fetch saw a document past the current page.
+    pub has_next_page: bool,
+}
+
+impl Default for PaginationState {
+    fn default() -> Self {
+        Self {
+            current_page: 0,
+            page_size: 10,
+            total_count: None,
+            count_estimated: false,
+            has_next_page: false,
+        }
+    }
+}
+
+/// Guided sort entry: pick a field and a direction, one key at a time.
+/// The sort is kept as a BSON document so keys stay in insertion order.
+#[derive(Debug, Clone)]
+pub struct SortPicker {
+    pub fields: Vec<String>,
+    pub state: ListState,
+    pub ascending: bool,
+    pub sort: Document,
+}
+
+/// Comparison offered by the guided filter builder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Gt,
+    Lt,
+    In,
+    Regex,
+    Exists,
+}
+
+impl FilterOp {
+    const ALL: [FilterOp; 7] = [
+        FilterOp::Eq,
+        FilterOp::Ne,
+        FilterOp::Gt,
+        FilterOp::Lt,
+        FilterOp::In,
+        FilterOp::Regex,
+        FilterOp::Exists,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FilterOp::Eq => "=",
+            FilterOp::Ne => "!=",
+            FilterOp::Gt => ">",
+            FilterOp::Lt => "<",
+            FilterOp::In => "in",
+            FilterOp::Regex => "regex",
+            FilterOp::Exists => "exists",
+        }
+    }
+
+    /// Neighbouring operator, wrapping around; `step` is 1 or -1.
+    pub fn cycle(self, step: isize) -> Self {
+        let i = Self::ALL.iter().position(|op| *op == self).unwrap_or(0) as isize;
+        let len = Self::ALL.len() as isize;
+        Self::ALL[(i + step).rem_euclid(len) as usize]
+    }
+}
+
+/// One condition of the guided filter: `field` indexes the builder's fields.
+#[derive(Debug, Clone)]
+pub struct FilterRow {
+    pub field: usize,
+    pub op: FilterOp,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterColumn {
+    Field,
+    Operator,
+    Value,
+}
+
+/// Guided filter entry: rows of field, operator and value, ANDed together.
+/// Values are typed after the field's inferred schema type.
+#[derive(Debug, Clone)]
+pub struct FilterBuilder {
+    /// Field paths with their inferred type name.
+    pub fields: Vec<(String, String)>,
+    pub rows: Vec<FilterRow>,
+    pub selected: usize,
+    pub column: FilterColumn,
+}
+
+/// Raw text of the query inputs, remembered per collection and kept in the
+/// query history.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct QueryState {
+    pub filter: String,
+    pub sort: String,
+    pub projection: String,
+    pub limit: String,
+}
+
+impl From<&NamedQuery> for QueryState {
+    fn from(query: &NamedQuery) -> Self {
+        Self {
+            filter: query.filter.clone(),
+            sort: query.sort.clone(),
+            projection: query.projection.clone(),
+            limit: query.limit.clone(),
+        }
+    }
+}
+
+/// Inline edit of a loaded document in the JSON viewer.
+#[derive(Debug, Clone)]
+pub struct DocumentEdit {
+    pub input: Box<TextArea<'static>>,
+    /// The document as loaded, to diff against when saving.
+    pub original: Document,
+    pub error: Option<String>,
+}
+
+/// What must be typed to update every document of a collection.
+pub const UPDATE_ALL_PHRASE: &str = "update ALL documents";
+
+/// What a single-line [`PopupState::Prompt`] submits to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptKind {
+    JumpToRow,
+    GoToPage,
+    ExportPath,
+    /// Name for the query being saved.
+    SaveQuery,
+}
+
+impl PromptKind {
+    pub fn title(&self) -> &'static str {
+        match self {
+            PromptKind::JumpToRow => "Go to Row",
+            PromptKind::GoToPage => "Go to Page",
+            PromptKind::ExportPath => "Export to File (.json or .csv)",
+            PromptKind::SaveQuery => "Save Query As",
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -28,13 +221,110 @@ pub enum PopupState {
     ConnectionManager {
         name: Box<TextArea<'static>>,
         uri: Box<TextArea<'static>>,
-        is_editing_uri: bool,
+        pool_size: Box<TextArea<'static>>,
+        auth_source: Box<TextArea<'static>>,
+        app_name: Box<TextArea<'static>>,
+        tls: bool,
+        active_field: ConnectionField,
+        error: Option<String>,
+        /// Index of the connection being edited; `None` when adding one.
+        editing: Option<usize>,
+        /// Outcome of the last Ctrl-t test: `Ok(latency)` or `Err(message)`,
+        /// with `Ok(None)` while it is still running.
+        test_result: Option<Result<Option<u64>, String>>,
     },
     QueryBuilder {
         active_field: QueryField,
+        sort_picker: Option<SortPicker>,
+        filter_builder: Option<FilterBuilder>,
+        // Filter text when the builder opened, to tell if the page must reset
+        original_filter: String,
+    },
+    CommandRunner {
+        db: Box<TextArea<'static>>,
+        command: Box<TextArea<'static>>,
+        is_editing_command: bool,
+        error: Option<String>,
+        /// Destructive command shown back to the user, run on the next Enter.
+        armed: Option<String>,
+    },
+    /// Editor for a new document in the selected collection.
+    InsertDocument {
+        input: Box<TextArea<'static>>,
+        error: Option<String>,
+    },
+    /// json, doc_id, offset, visual line selection as `(anchor, cursor)`,
+    /// and the editor when opened for editing
+    JsonViewer(
+        String,
+        String,
+        usize,
+        Option<(usize, usize)>,
+        Option<DocumentEdit>,
+    ),
+    /// A document as a collapsible tree.
+    JsonTree(Box<JsonTree>),
+    /// Distinct values of a field; picking one narrows the filter to it.
+    DistinctValues {
+        field: String,
+        values: Vec<Bson>,
+        state: ListState,
+        /// Set when the server returned more than the list shows.
+        truncated: bool,
     },
-    JsonViewer(String, String, usize), // json, doc_id, offset
+    /// Index specs of the selected collection.
+    Indexes(TableState, Vec<Document>),
+    /// `collStats` reply for a collection, keyed by its namespace.
+    CollectionStats(String, Document),
+    /// Output of `explain`: a summary, or the raw reply scrolled to `offset`.
+    Explain {
+        explain: Document,
+        raw: bool,
+        offset: usize,
+    },
+    /// Drop of a collection, or of a whole database when `collection` is
+    /// `None`; runs only once its name is typed exactly.
+    ConfirmDrop {
+        db: String,
+        collection: Option<String>,
+        input: Box<TextArea<'static>>,
+        error: Option<String>,
+    },
+    /// Filter and update document applied to every matching document.
+    BulkUpdate {
+        filter: Box<TextArea<'static>>,
+        update: Box<TextArea<'static>>,
+        is_editing_update: bool,
+        error: Option<String>,
+    },
+    /// Update with an empty filter; runs only once [`UPDATE_ALL_PHRASE`] is
+    /// typed.
+    ConfirmUpdateAll {
+        db: String,
+        coll: String,
+        update: Document,
+        count: u64,
+        input: Box<TextArea<'static>>,
+        error: Option<String>,
+    },
+    /// Past queries, newest first; Enter loads the selected one.
+    QueryHistory(ListState),
+    /// Queries saved in the config, by name.
+    SavedQueries(ListState),
+    /// JSON array of pipeline stages for the selected collection.
+    AggregationBuilder,
     FieldSelector(ListState, Vec<String>, Vec<String>), // State, All, Visible
     Help(TableState),
     Error(String),
+    ConfirmQuit(Box<PopupState>), // Popup to return to on cancel
+    /// Yes/no question; `on_confirm` is dispatched on `y`/Enter.
+    Confirm {
+        message: String,
+        on_confirm: Box<Action>,
+    },
+    Prompt {
+        kind: PromptKind,
+        input: Box<TextArea<'static>>,
+        error: Option<String>,
+    },
 }
@@ -0,0 +1,143 @@
+//! Writing query results to files.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use mongo_core::bson::{Bson, Document};
+use serde::{Deserialize, Serialize};
+
+use super::format::format_cell;
+
+/// File layout of an export.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    /// A JSON array of relaxed Extended JSON documents, as `mongoexport
+    /// --jsonArray` writes it, so ObjectIds and dates survive a re-import.
+    Json,
+    /// One row per document with the given columns, rendered like the table
+    /// cells.
+    Csv(Vec<String>),
+}
+
+impl ExportFormat {
+    /// Picks the format from the file extension; anything but `.csv` is JSON.
+    pub fn for_path(path: &Path, columns: &[String]) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => ExportFormat::Csv(columns.to_vec()),
+            _ => ExportFormat::Json,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv(_) => "csv",
+        }
+    }
+}
+
+/// Streams documents to a temporary file next to the target, which is only
+/// renamed into place by [`ExportWriter::finish`]. Dropping the writer
+/// before that (on error or cancel) removes the temporary file, so the
+/// target is never left half-written.
+pub struct ExportWriter {
+    format: ExportFormat,
+    out: BufWriter<File>,
+    path: PathBuf,
+    tmp_path: PathBuf,
+    written: u64,
+    finished: bool,
+}
+
+impl ExportWriter {
+    pub fn create(path: &Path, format: ExportFormat) -> io::Result<Self> {
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No file name"))?;
+        let tmp_path = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+        let mut out = BufWriter::new(File::create(&tmp_path)?);
+        match &format {
+            ExportFormat::Json => out.write_all(b"[")?,
+            ExportFormat::Csv(columns) => write_csv_row(&mut out, columns)?,
+        }
+        Ok(Self {
+            format,
+            out,
+            path: path.to_path_buf(),
+            tmp_path,
+            written: 0,
+            finished: false,
+        })
+    }
+
+    pub fn write(&mut self, doc: &Document) -> io::Result<()> {
+        match &self.format {
+            ExportFormat::Json => {
+                let separator: &[u8] = if self.written == 0 { b"\n" } else { b",\n" };
+                self.out.write_all(separator)?;
+                let value = Bson::Document(doc.clone()).into_relaxed_extjson();
+                serde_json::to_writer_pretty(&mut self.out, &value)?;
+            }
+            ExportFormat::Csv(columns) => {
+                let cells: Vec<String> = columns.iter().map(|c| format_cell(doc, c)).collect();
+                write_csv_row(&mut self.out, &cells)?;
+            }
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Documents written so far.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Completes the file and moves it over the target path.
+    pub fn finish(mut self) -> io::Result<u64> {
+        match self.format {
+            ExportFormat::Json => self.out.write_all(b"\n]\n")?,
+            ExportFormat::Csv(_) => {}
+        }
+        self.out.flush()?;
+        self.out.get_ref().sync_all()?;
+        fs::rename(&self.tmp_path, &self.path)?;
+        self.finished = true;
+        Ok(self.written)
+    }
+}
+
+impl Drop for ExportWriter {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = fs::remove_file(&self.tmp_path);
+        }
+    }
+}
+
+/// Writes one CSV record. Following RFC 4180, fields containing commas,
+/// quotes or line breaks are quoted, with inner quotes doubled, and records
+/// end in CRLF.
+fn write_csv_row(out: &mut impl Write, cells: &[String]) -> io::Result<()> {
+    for (i, cell) in cells.iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+        if cell.contains([',', '"', '\r', '\n']) {
+            write!(out, "\"{}\"", cell.replace('"', "\"\""))?;
+        } else {
+            out.write_all(cell.as_bytes())?;
+        }
+    }
+    out.write_all(b"\r\n")
+}
+
+/// Resolves a typed path, expanding a leading `~/` to the home directory.
+pub fn expand_path(input: &str) -> PathBuf {
+    match (input.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(input),
+    }
+}
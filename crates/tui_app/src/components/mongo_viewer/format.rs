@@ -0,0 +1,145 @@
+use mongo_core::bson::{Bson, Document};
+pub use mongo_core::bson_type_name;
+
+/// Synthetic column showing when an ObjectId `_id` was generated.
+pub const CREATED_COLUMN: &str = "(created)";
+
+/// Single-line rendering of a value for table cells. Dates show as UTC
+/// `YYYY-MM-DD HH:MM:SS` and ObjectIds as bare hex.
+pub fn format_bson_cell(value: &Bson) -> String {
+    match value {
+        Bson::DateTime(date) => match date.try_to_rfc3339_string() {
+            Ok(iso) if iso.len() >= 19 => iso[..19].replacen('T', " ", 1),
+            _ => date.to_string(),
+        },
+        Bson::ObjectId(id) => id.to_hex(),
+        Bson::Decimal128(d) => d.to_string(),
+        Bson::Binary(bin) => format!(
+            "Binary({:#x}, {} bytes)",
+            u8::from(bin.subtype),
+            bin.bytes.len()
+        ),
+        other => other.to_string(),
+    }
+}
+
+/// Full rendering of a value for the clipboard. Binary data is copied as base64.
+pub fn format_bson_copy(value: &Bson) -> String {
+    match value {
+        Bson::Decimal128(d) => d.to_string(),
+        Bson::Binary(_) => value
+            .clone()
+            .into_relaxed_extjson()
+            .pointer("/$binary/base64")
+            .and_then(|b| b.as_str())
+            .map(str::to_string)
+            .unwrap_or_default(),
+        other => other.to_string(),
+    }
+}
+
+/// Creation time embedded in the document's ObjectId `_id`, as RFC 3339 UTC.
+pub fn object_id_created(doc: &Document) -> Option<String> {
+    let id = doc.get_object_id("_id").ok()?;
+    id.timestamp().try_to_rfc3339_string().ok()
+}
+
+/// Value at a dotted `path` such as `address.city` or `items.0.sku`, walking
+/// sub-documents and numeric array indices. A key that itself contains dots
+/// is matched as-is first. Returns `None` as soon as a step is missing.
+pub fn get_nested<'a>(doc: &'a Document, path: &str) -> Option<&'a Bson> {
+    if let Some(value) = doc.get(path) {
+        return Some(value);
+    }
+    let mut segments = path.split('.');
+    let mut current = doc.get(segments.next()?)?;
+    for segment in segments {
+        current = match current {
+            Bson::Document(sub) => sub.get(segment)?,
+            Bson::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+/// Table cell text for `field`, including synthetic columns.
+pub fn format_cell(doc: &Document, field: &str) -> String {
+    if field == CREATED_COLUMN {
+        return object_id_created(doc).unwrap_or_default();
+    }
+    get_nested(doc, field)
+        .map(format_bson_cell)
+        .unwrap_or_default()
+}
+
+/// Cell text as drawn in the table: integers optionally grouped by
+/// thousands, and anything wider than `width` cut with an ellipsis.
+pub fn display_cell(doc: &Document, field: &str, width: usize, group_digits: bool) -> String {
+    let text = match get_nested(doc, field) {
+        Some(Bson::Int32(n)) if group_digits => format_signed(*n as i64),
+        Some(Bson::Int64(n)) if group_digits => format_signed(*n),
+        _ => format_cell(doc, field),
+    };
+    truncate(text, width)
+}
+
+fn format_signed(n: i64) -> String {
+    let digits = format_count(n.unsigned_abs());
+    if n < 0 {
+        format!("-{}", digits)
+    } else {
+        digits
+    }
+}
+
+/// `text` cut to `width` characters, the last one replaced by `…`.
+fn truncate(text: String, width: usize) -> String {
+    if width == 0 || text.chars().count() <= width {
+        return text;
+    }
+    let mut out: String = text.chars().take(width - 1).collect();
+    out.push('…');
+    out
+}
+
+/// Byte count in binary units, e.g. `1.5 MiB`.
+pub fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", value, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Count with thousands separators, e.g. `12,340`.
+pub fn format_count(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Escapes regex metacharacters so `value` matches literally in `$regex`.
+pub fn escape_regex(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
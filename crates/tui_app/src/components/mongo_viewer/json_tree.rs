@@ -0,0 +1,163 @@
+//! Collapsible tree view of a single document.
+
+use std::collections::HashSet;
+
+use mongo_core::bson::{Bson, Document};
+
+/// Arrays longer than this start collapsed.
+const LARGE_ARRAY_ITEMS: usize = 20;
+
+/// One visible row of the tree. Nodes are addressed by the position of each
+/// step from the root, so field names containing dots can't collide.
+pub struct TreeLine<'a> {
+    pub depth: usize,
+    pub path: Vec<usize>,
+    /// Field name, or `[i]` for array items.
+    pub key: String,
+    pub value: &'a Bson,
+    pub expanded: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct JsonTree {
+    pub title: String,
+    pub root: Document,
+    expanded: HashSet<Vec<usize>>,
+    /// Index of the focused line among the visible ones.
+    pub cursor: usize,
+    /// First line drawn; kept around the cursor when drawing.
+    pub offset: usize,
+}
+
+impl JsonTree {
+    /// Starts with every object and array open except large arrays.
+    pub fn new(root: Document, title: String) -> Self {
+        let mut expanded = HashSet::new();
+        for (i, (_, value)) in root.iter().enumerate() {
+            expand_defaults(value, vec![i], &mut expanded);
+        }
+        Self {
+            title,
+            root,
+            expanded,
+            cursor: 0,
+            offset: 0,
+        }
+    }
+
+    /// The rows currently visible, in display order.
+    pub fn lines(&self) -> Vec<TreeLine<'_>> {
+        let mut lines = vec![];
+        let entries = self.root.iter().map(|(k, v)| (k.clone(), v)).collect();
+        self.push_lines(entries, 0, &[], &mut lines);
+        lines
+    }
+
+    fn push_lines<'a>(
+        &self,
+        entries: Vec<(String, &'a Bson)>,
+        depth: usize,
+        parent: &[usize],
+        out: &mut Vec<TreeLine<'a>>,
+    ) {
+        for (i, (key, value)) in entries.into_iter().enumerate() {
+            let mut path = parent.to_vec();
+            path.push(i);
+            let expanded = self.expanded.contains(&path);
+            out.push(TreeLine {
+                depth,
+                path: path.clone(),
+                key,
+                value,
+                expanded,
+            });
+            if expanded {
+                self.push_lines(children(value), depth + 1, &path, out);
+            }
+        }
+    }
+
+    pub fn move_cursor(&mut self, delta: isize) {
+        let last = self.lines().len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(last);
+    }
+
+    /// Opens or closes the object or array under the cursor.
+    pub fn toggle(&mut self) {
+        let Some(path) = self.focused_container() else {
+            return;
+        };
+        if !self.expanded.remove(&path) {
+            self.expanded.insert(path);
+        }
+    }
+
+    pub fn expand(&mut self) {
+        if let Some(path) = self.focused_container() {
+            self.expanded.insert(path);
+        }
+    }
+
+    /// Closes the node under the cursor, or moves to its parent when it is
+    /// already closed or a plain value.
+    pub fn collapse(&mut self) {
+        let lines = self.lines();
+        let Some(line) = lines.get(self.cursor) else {
+            return;
+        };
+        if line.expanded {
+            let path = line.path.clone();
+            self.expanded.remove(&path);
+        } else if let Some((_, parent)) = line.path.split_last() {
+            if let Some(idx) = lines.iter().position(|l| l.path == parent) {
+                self.cursor = idx;
+            }
+        }
+    }
+
+    /// Field name and value under the cursor.
+    pub fn focused(&self) -> Option<(String, Bson)> {
+        self.lines()
+            .into_iter()
+            .nth(self.cursor)
+            .map(|line| (line.key, line.value.clone()))
+    }
+
+    fn focused_container(&self) -> Option<Vec<usize>> {
+        self.lines()
+            .into_iter()
+            .nth(self.cursor)
+            .filter(|line| !children(line.value).is_empty())
+            .map(|line| line.path)
+    }
+}
+
+/// Fields of a document or items of an array; empty for anything else.
+fn children(value: &Bson) -> Vec<(String, &Bson)> {
+    match value {
+        Bson::Document(doc) => doc.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        Bson::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (format!("[{}]", i), v))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn expand_defaults(value: &Bson, path: Vec<usize>, expanded: &mut HashSet<Vec<usize>>) {
+    let open = match value {
+        Bson::Document(doc) => !doc.is_empty(),
+        Bson::Array(items) => !items.is_empty() && items.len() <= LARGE_ARRAY_ITEMS,
+        _ => false,
+    };
+    if !open {
+        return;
+    }
+    for (i, (_, child)) in children(value).into_iter().enumerate() {
+        let mut child_path = path.clone();
+        child_path.push(i);
+        expand_defaults(child, child_path, expanded);
+    }
+    expanded.insert(path);
+}
@@ -1,7 +1,8 @@
 // use std::rc::Rc;
 // use std::cell::RefCell;
 use color_eyre::eyre::Result;
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use futures::{StreamExt, TryStreamExt};
 use ratatui::{
     prelude::*,
     widgets::{
@@ -9,9 +10,18 @@ use ratatui::{
         TableState, Wrap,
     },
 };
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 use tokio::sync::mpsc::UnboundedSender;
 // use tracing::{info, error};
 use lazy_static::lazy_static;
+use mongo_core::bson::{doc, Bson};
+use mongo_core::DocumentCount;
 use syntect::{
     easy::HighlightLines, highlighting::ThemeSet, parsing::SyntaxSet, util::LinesWithEndings,
 };
@@ -19,28 +29,76 @@ use syntect_tui::into_span;
 use tui_textarea::TextArea;
 
 use super::Component;
-use crate::{action::Action, config::Config};
+use crate::{
+    action::Action,
+    app::Mode,
+    config::{self, Config, NamedQuery},
+    logging,
+};
 
 pub mod context;
 pub mod defs;
+pub mod export;
+pub mod format;
+pub mod json_tree;
 pub mod pane_id;
 pub mod parts;
 pub mod registry;
+pub mod tabs;
+pub mod theme;
+pub mod util;
 
 use context::MongoContext;
-use defs::{PopupState, QueryField};
+use defs::{
+    ConnectionField, DocumentEdit, FilterBuilder, FilterColumn, FilterOp, FilterRow, LoadingKind,
+    PopupState, PromptKind, QueryField, QueryState, SortPicker, UPDATE_ALL_PHRASE,
+};
+use export::{ExportFormat, ExportWriter};
+use format::{format_bson_cell, format_bson_copy, format_bytes, format_count};
+use json_tree::JsonTree;
 use pane_id::PaneId;
 use parts::{
     connections::ConnectionsPane, databases::DatabasesPane, documents::DocumentsPane,
-    query::QueryPane,
+    query::QueryPane, schema::SchemaPane,
 };
 use registry::PaneRegistry;
+use tabs::{tab_label, tab_sender, Tab};
+
+/// How often the open connection is pinged to keep the status dot current.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Exported documents between progress updates in the status line.
+const EXPORT_PROGRESS_EVERY: u64 = 500;
+
+/// Documents sampled to infer the collection's field paths.
+const SCHEMA_SAMPLE_SIZE: i64 = 50;
+
+/// Distinct values shown at most; high-cardinality fields get cut here.
+const DISTINCT_LIMIT: usize = 500;
+
+/// Lines of `app.log` shown by the log viewer.
+const APP_LOG_LINES: usize = 1000;
+
+/// How long Ctrl-t in the connection form waits for the server.
+const CONNECTION_TEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Sidebar share reachable with Ctrl-Left/Right, and the step per press.
+const SIDEBAR_PERCENT_RANGE: (u16, u16) = (10, 60);
+const SIDEBAR_PERCENT_STEP: u16 = 5;
+
+/// Columns and rows kept for each pane however small the terminal gets.
+const MIN_SIDEBAR_WIDTH: u16 = 16;
+const MIN_DOCUMENTS_WIDTH: u16 = 30;
+const MIN_QUERY_ROWS: u16 = 3;
+const MIN_RESULTS_ROWS: u16 = 5;
 
 lazy_static! {
     static ref SYNTAX_SET: SyntaxSet = SyntaxSet::load_defaults_newlines();
     static ref THEME_SET: ThemeSet = ThemeSet::load_defaults();
 }
 
+const JSON_THEME: &str = "base16-ocean.dark";
+
 pub struct MongoViewer {
     context: MongoContext,
     registry: PaneRegistry,
@@ -51,15 +109,65 @@ pub struct MongoViewer {
     db_pane_id: PaneId,
     query_pane_id: PaneId,
     doc_pane_id: PaneId,
+    schema_pane_id: PaneId,
+
+    // Set while an editing popup holds text that has not been submitted
+    has_unsaved_edits: bool,
+
+    // Keys typed so far towards a multi-key binding from the config
+    pending_keys: Vec<KeyEvent>,
+
+    // Connection heartbeat
+    last_heartbeat: Instant,
+    heartbeat_in_flight: bool,
+
+    // Set while an export runs; raising the flag makes it stop
+    export_cancel: Option<Arc<AtomicBool>>,
+    // Table columns when the export prompt opened, used for CSV
+    export_columns: Vec<String>,
+
+    // Task forwarding change stream events while watching
+    watch_task: Option<tokio::task::JoinHandle<()>>,
+
+    // Open connections; the active one's context and panes are the fields
+    // above
+    tabs: Vec<Tab>,
+    active_tab: usize,
+    next_tab_id: usize,
+    // The app's channel, which each tab's sender forwards into
+    action_tx: Option<UnboundedSender<Action>>,
+}
+
+/// The five panes of a tab, under ids shared by every tab.
+fn build_registry(
+    conn_pane_id: PaneId,
+    db_pane_id: PaneId,
+    query_pane_id: PaneId,
+    doc_pane_id: PaneId,
+    schema_pane_id: PaneId,
+) -> PaneRegistry {
+    let mut registry = PaneRegistry::new();
+    registry.register(ConnectionsPane::new(conn_pane_id));
+    registry.register(DatabasesPane::new(db_pane_id));
+    registry.register(QueryPane::new(query_pane_id));
+    registry.register(DocumentsPane::new(doc_pane_id));
+    registry.register(SchemaPane::new(schema_pane_id));
+
+    // Set initial active
+    registry.set_active(conn_pane_id);
+    registry
+}
 
-    // Loading State
-    is_loading: bool,
-    loading_frame: usize,
+/// Settings from the config that each tab's context carries.
+fn apply_config(context: &mut MongoContext, config: Config) {
+    context.theme = theme::Theme::new(config.config.no_color, &config.styles);
+    context.clipboard = context::ClipboardBackend::new(config.config.clipboard);
+    context.pagination.page_size = config.config.page_size.max(1);
+    context.config = config;
 }
 
 impl Default for MongoViewer {
     fn default() -> Self {
-        let mut registry = PaneRegistry::new();
         let context = MongoContext::new();
 
         // Create Panes
@@ -67,14 +175,14 @@ impl Default for MongoViewer {
         let db_pane_id = PaneId::new();
         let query_pane_id = PaneId::new();
         let doc_pane_id = PaneId::new();
-
-        registry.register(ConnectionsPane::new(conn_pane_id));
-        registry.register(DatabasesPane::new(db_pane_id));
-        registry.register(QueryPane::new(query_pane_id));
-        registry.register(DocumentsPane::new(doc_pane_id));
-
-        // Set initial active
-        registry.set_active(conn_pane_id);
+        let schema_pane_id = PaneId::new();
+        let registry = build_registry(
+            conn_pane_id,
+            db_pane_id,
+            query_pane_id,
+            doc_pane_id,
+            schema_pane_id,
+        );
 
         Self {
             context,
@@ -84,8 +192,18 @@ impl Default for MongoViewer {
             db_pane_id,
             query_pane_id,
             doc_pane_id,
-            is_loading: false,
-            loading_frame: 0,
+            schema_pane_id,
+            has_unsaved_edits: false,
+            pending_keys: Vec::new(),
+            last_heartbeat: Instant::now(),
+            heartbeat_in_flight: false,
+            export_cancel: None,
+            export_columns: vec![],
+            watch_task: None,
+            tabs: vec![Tab::new(0, None)],
+            active_tab: 0,
+            next_tab_id: 1,
+            action_tx: None,
         }
     }
 }
@@ -95,12 +213,550 @@ impl MongoViewer {
         Self::default()
     }
 
+    /// Opens an empty tab with the active tab's connections and settings, and
+    /// switches to it.
+    fn open_tab(&mut self) -> Result<()> {
+        let id = self.next_tab_id;
+        self.next_tab_id += 1;
+        let mut context = MongoContext::new();
+        context.connections = self.context.connections.clone();
+        apply_config(&mut context, self.context.config.clone());
+        context.action_tx = self.action_tx.clone().map(|tx| tab_sender(id, tx));
+        let registry = build_registry(
+            self.conn_pane_id,
+            self.db_pane_id,
+            self.query_pane_id,
+            self.doc_pane_id,
+            self.schema_pane_id,
+        );
+        self.tabs.push(Tab::new(id, Some((context, registry))));
+        self.switch_tab(self.tabs.len() - 1)
+    }
+
+    /// Parks the active tab and brings up tab `idx`, replaying what its tasks
+    /// sent while it was in the background.
+    fn switch_tab(&mut self, idx: usize) -> Result<()> {
+        if idx == self.active_tab {
+            return Ok(());
+        }
+        let Some((mut context, registry)) = self.tabs.get_mut(idx).and_then(|t| t.parked.take())
+        else {
+            return Ok(());
+        };
+        // Connections and settings are shared whichever tab changed them
+        context.connections = self.context.connections.clone();
+        context.config = self.context.config.clone();
+        // The change stream only feeds the tab on screen
+        if let Some(task) = self.watch_task.take() {
+            task.abort();
+            self.context.watching = None;
+        }
+        let previous = (
+            std::mem::replace(&mut self.context, context),
+            std::mem::replace(&mut self.registry, registry),
+        );
+        self.tabs[self.active_tab].parked = Some(previous);
+        self.active_tab = idx;
+        self.heartbeat_in_flight = false;
+        self.last_heartbeat = Instant::now();
+
+        for action in std::mem::take(&mut self.tabs[idx].pending) {
+            if let Some(next) = self.update(action)? {
+                if let Some(tx) = &self.action_tx {
+                    let _ = tx.send(next);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves `step` tabs over, wrapping around. Refused during an export,
+    /// whose progress belongs to the tab it started in.
+    fn cycle_tab(&mut self, step: isize) -> Result<()> {
+        if self.tabs.len() < 2 {
+            self.context.set_status("No other tab open");
+            return Ok(());
+        }
+        if self.export_cancel.is_some() {
+            self.context
+                .set_status("Finish or cancel the export first (Esc)");
+            return Ok(());
+        }
+        let len = self.tabs.len() as isize;
+        let idx = (self.active_tab as isize + step).rem_euclid(len) as usize;
+        self.switch_tab(idx)
+    }
+
+    /// Closes the active tab and its client, moving to the next one.
+    fn close_tab(&mut self) -> Result<()> {
+        if self.tabs.len() < 2 {
+            self.context.set_status("Only one tab open");
+            return Ok(());
+        }
+        if self.export_cancel.is_some() {
+            self.context
+                .set_status("Finish or cancel the export first (Esc)");
+            return Ok(());
+        }
+        let closing = self.active_tab;
+        let next = if closing + 1 < self.tabs.len() {
+            closing + 1
+        } else {
+            closing - 1
+        };
+        self.switch_tab(next)?;
+        let closed = self.tabs.remove(closing);
+        if let Some((mut context, _)) = closed.parked {
+            context.cancel_query();
+            let name = tab_label(&context);
+            self.context.set_status(format!("Closed tab {}", name));
+        }
+        if closing < self.active_tab {
+            self.active_tab -= 1;
+        }
+        Ok(())
+    }
+
+    /// One line listing the open tabs, the active one highlighted.
+    fn draw_tab_bar(&self, f: &mut Frame, area: Rect) {
+        let mut spans = vec![];
+        for (i, tab) in self.tabs.iter().enumerate() {
+            let (label, style) = match &tab.parked {
+                Some((context, _)) => (tab_label(context), self.context.theme.muted()),
+                None => (tab_label(&self.context), self.context.theme.accent()),
+            };
+            if i > 0 {
+                spans.push(Span::styled("│", self.context.theme.muted()));
+            }
+            let marker = if tab.pending.is_empty() { "" } else { " •" };
+            spans.push(Span::styled(
+                format!(" {}:{}{} ", i + 1, label, marker),
+                style,
+            ));
+        }
+        f.render_widget(Line::from(spans), area);
+    }
+
+    /// Opens the connection form, empty or pre-filled from the connection at
+    /// `editing`.
+    fn open_connection_manager(&mut self, editing: Option<usize>) {
+        let existing = editing.and_then(|idx| self.context.connections.get(idx));
+        let mut name = TextArea::default();
+        name.set_placeholder_text("Connection Name");
+        let mut uri = TextArea::default();
+        uri.set_placeholder_text("mongodb://localhost:27017");
+        let mut pool_size = TextArea::default();
+        pool_size.set_placeholder_text("Driver default");
+        let mut auth_source = TextArea::default();
+        auth_source.set_placeholder_text("From URI");
+        let mut app_name = TextArea::default();
+        app_name.set_placeholder_text("From URI");
+        if let Some(conn) = existing {
+            name.insert_str(&conn.name);
+            uri.insert_str(&conn.uri);
+            if let Some(size) = conn.max_pool_size {
+                pool_size.insert_str(size.to_string());
+            }
+            if let Some(source) = &conn.auth_source {
+                auth_source.insert_str(source);
+            }
+            if let Some(app) = &conn.app_name {
+                app_name.insert_str(app);
+            }
+        }
+        self.popup_state = PopupState::ConnectionManager {
+            name: Box::new(name),
+            uri: Box::new(uri),
+            pool_size: Box::new(pool_size),
+            auth_source: Box::new(auth_source),
+            app_name: Box::new(app_name),
+            tls: existing.is_some_and(|c| c.tls),
+            active_field: ConnectionField::Name,
+            error: None,
+            editing: existing.and(editing),
+            test_result: None,
+        };
+    }
+
+    /// Feeds `key` to the keybindings from the config. Returns the bound action
+    /// once a sequence completes, or `Render` while a longer one is pending.
+    fn match_keybinding(&mut self, key: KeyEvent) -> Option<Action> {
+        let keybindings = &self.context.config.keybindings;
+        let bindings = keybindings.0.get(&Mode::Home)?;
+        // Terminals may report extra state; bindings only carry code and modifiers
+        let key = KeyEvent::new(key.code, key.modifiers);
+        self.pending_keys.push(key);
+        if let Some(action) = bindings.get(&self.pending_keys) {
+            self.pending_keys.clear();
+            return Some(action.clone());
+        }
+        if keybindings.is_prefix(Mode::Home, &self.pending_keys) {
+            return Some(Action::Render);
+        }
+        // The key that broke a sequence may still start a new one
+        let broke_sequence = self.pending_keys.len() > 1;
+        self.pending_keys.clear();
+        if broke_sequence {
+            return self.match_keybinding(key);
+        }
+        None
+    }
+
+    /// Infers the field paths of a collection in the background.
+    fn load_schema(&self, db_name: String, coll_name: String) {
+        let mongo_core = self.context.mongo_core.clone();
+        let Some(tx) = self.context.action_tx.clone() else {
+            return;
+        };
+        tokio::spawn(async move {
+            match mongo_core
+                .infer_schema(&db_name, &coll_name, SCHEMA_SAMPLE_SIZE)
+                .await
+            {
+                Ok(fields) => {
+                    let _ = tx.send(Action::SchemaLoaded(fields));
+                }
+                // The columns still come from the loaded documents, so a
+                // failed sample is not worth a popup
+                Err(e) => {
+                    let _ = tx.send(Action::SchemaFailed(e.to_string()));
+                }
+            }
+        });
+    }
+
+    /// Writes the saved (non-transient) connections to the config file,
+    /// showing an error popup if it cannot be written.
+    fn persist_connections(&mut self) {
+        self.context.config.config.connections = self
+            .context
+            .connections
+            .iter()
+            .filter(|c| !c.transient)
+            .cloned()
+            .collect();
+        if let Err(e) = self.context.config.save() {
+            self.popup_state = PopupState::Error(format!("Failed to save config: {}", e));
+        }
+    }
+
+    /// Opens the popups a pane asks for and passes any other action on.
+    fn dispatch_pane_action(&mut self, result: Option<Action>) -> Result<Option<Action>> {
+        if let Some(action) = result {
+            // Handle internal actions immediately
+            match action {
+                Action::OpenConnectionManager => {
+                    self.open_connection_manager(None);
+                    return Ok(Some(Action::Render));
+                }
+                Action::OpenConnectionEditor(idx) => {
+                    self.open_connection_manager(Some(idx));
+                    return Ok(Some(Action::Render));
+                }
+                Action::OpenJumpToRow => {
+                    let mut input = TextArea::default();
+                    input.set_placeholder_text("Row number");
+                    self.popup_state = PopupState::Prompt {
+                        kind: PromptKind::JumpToRow,
+                        input: Box::new(input),
+                        error: None,
+                    };
+                    return Ok(Some(Action::Render));
+                }
+                Action::OpenGoToPage => {
+                    let mut input = TextArea::default();
+                    input.set_placeholder_text(match self.context.page_count() {
+                        Some(pages) => format!("Page number (1-{})", pages),
+                        None => "Page number".to_string(),
+                    });
+                    self.popup_state = PopupState::Prompt {
+                        kind: PromptKind::GoToPage,
+                        input: Box::new(input),
+                        error: None,
+                    };
+                    return Ok(Some(Action::Render));
+                }
+                Action::OpenExport(columns) => {
+                    let Some((_, coll_name)) = self.context.selected_namespace() else {
+                        self.context.set_status("Select a collection first");
+                        return Ok(Some(Action::Render));
+                    };
+                    self.export_columns = columns.clone();
+                    let input = TextArea::from([format!(
+                        "{}.{}",
+                        coll_name,
+                        ExportFormat::Json.extension()
+                    )]);
+                    self.popup_state = PopupState::Prompt {
+                        kind: PromptKind::ExportPath,
+                        input: Box::new(input),
+                        error: None,
+                    };
+                    return Ok(Some(Action::Render));
+                }
+                Action::OpenInsertDocument => {
+                    if self.context.selected_namespace().is_none() {
+                        self.context.set_status("Select a collection first");
+                        return Ok(Some(Action::Render));
+                    }
+                    let mut input = TextArea::from(["{", "  ", "}"]);
+                    input.move_cursor(tui_textarea::CursorMove::Down);
+                    input.move_cursor(tui_textarea::CursorMove::End);
+                    self.popup_state = PopupState::InsertDocument {
+                        input: Box::new(input),
+                        error: None,
+                    };
+                    return Ok(Some(Action::Render));
+                }
+                Action::OpenBulkUpdate => {
+                    if self.context.selected_namespace().is_none() {
+                        self.context.set_status("Select a collection first");
+                        return Ok(Some(Action::Render));
+                    }
+                    // Starts from the current filter, usually what was browsed
+                    let mut filter = TextArea::from(self.context.query_input.lines().to_vec());
+                    filter.set_placeholder_text("{} (all documents)");
+                    let mut update = TextArea::from(["{ \"$set\": {  } }"]);
+                    // Inside the `$set` braces
+                    update.move_cursor(tui_textarea::CursorMove::Jump(0, 12));
+                    self.popup_state = PopupState::BulkUpdate {
+                        filter: Box::new(filter),
+                        update: Box::new(update),
+                        is_editing_update: true,
+                        error: None,
+                    };
+                    return Ok(Some(Action::Render));
+                }
+                Action::OpenDocumentEditor(row) => {
+                    let Some(doc) = self.context.documents.get(row) else {
+                        return Ok(None);
+                    };
+                    let Some(id) = doc.get("_id") else {
+                        self.context
+                            .set_status("Document has no _id to save against");
+                        return Ok(Some(Action::Render));
+                    };
+                    let json = serde_json::to_string_pretty(doc).unwrap_or_default();
+                    let edit = DocumentEdit {
+                        input: Box::new(TextArea::from(json.lines())),
+                        original: doc.clone(),
+                        error: None,
+                    };
+                    self.popup_state =
+                        PopupState::JsonViewer(json, id.to_string(), 0, None, Some(edit));
+                    return Ok(Some(Action::Render));
+                }
+                Action::RequestDeleteDocument(row) => {
+                    let Some(id) = self.context.documents.get(row).and_then(|d| d.get("_id"))
+                    else {
+                        self.context.set_status("Document has no _id to delete by");
+                        return Ok(Some(Action::Render));
+                    };
+                    self.popup_state = PopupState::Confirm {
+                        message: format!("Delete document with _id {}?", id),
+                        on_confirm: Box::new(Action::DeleteDocument(doc! { "_id": id.clone() })),
+                    };
+                    return Ok(Some(Action::Render));
+                }
+                Action::RequestDrop(db, collection) => {
+                    self.popup_state = PopupState::ConfirmDrop {
+                        db,
+                        collection,
+                        input: Box::default(),
+                        error: None,
+                    };
+                    return Ok(Some(Action::Render));
+                }
+                Action::OpenAggregationBuilder => {
+                    if self.context.selected_namespace().is_none() {
+                        self.context.set_status("Select a collection first");
+                        return Ok(Some(Action::Render));
+                    }
+                    self.popup_state = PopupState::AggregationBuilder;
+                    return Ok(Some(Action::Render));
+                }
+                Action::DeleteConnection(idx) => {
+                    let Some(conn) = self.context.connections.get(idx) else {
+                        return Ok(None);
+                    };
+                    self.popup_state = PopupState::Confirm {
+                        message: format!("Remove connection '{}'?", conn.name),
+                        on_confirm: Box::new(Action::DeleteConnection(idx)),
+                    };
+                    return Ok(Some(Action::Render));
+                }
+                Action::Help => {
+                    let mut state = TableState::default();
+                    state.select(Some(0));
+                    self.popup_state = PopupState::Help(state);
+                    return Ok(Some(Action::Render));
+                }
+                Action::OpenQueryBuilder => {
+                    self.popup_state = PopupState::QueryBuilder {
+                        active_field: QueryField::Filter,
+                        sort_picker: None,
+                        filter_builder: None,
+                        original_filter: self.context.query_input.lines().join("\n"),
+                    };
+                    return Ok(Some(Action::Render));
+                }
+                Action::OpenSavedQueries => {
+                    if self.context.config.config.saved_queries.is_empty() {
+                        self.context
+                            .set_status("No saved queries (Ctrl-s in the query builder)");
+                        return Ok(Some(Action::Render));
+                    }
+                    let mut state = ListState::default();
+                    state.select(Some(0));
+                    self.popup_state = PopupState::SavedQueries(state);
+                    return Ok(Some(Action::Render));
+                }
+                Action::OpenQueryHistory => {
+                    if self.context.query_history.is_empty() {
+                        self.context.set_status("No queries run yet");
+                        return Ok(Some(Action::Render));
+                    }
+                    let mut state = ListState::default();
+                    state.select(Some(0));
+                    self.popup_state = PopupState::QueryHistory(state);
+                    return Ok(Some(Action::Render));
+                }
+                Action::OpenJsonPopup(json, title) => {
+                    self.popup_state = PopupState::JsonViewer(json, title, 0, None, None);
+                    return Ok(Some(Action::Render));
+                }
+                Action::OpenDocumentTree(doc, title) => {
+                    self.popup_state = PopupState::JsonTree(Box::new(JsonTree::new(doc, title)));
+                    return Ok(Some(Action::Render));
+                }
+                Action::OpenFieldSelector(all_fields, visible_fields) => {
+                    let mut state = ListState::default();
+                    state.select(Some(0));
+                    self.popup_state = PopupState::FieldSelector(
+                        state,
+                        all_fields.clone(),
+                        visible_fields.clone(),
+                    );
+                    return Ok(Some(Action::Render));
+                }
+                _ => return Ok(Some(action)),
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Sidebar width and query bar height for `area`, from the configured
+    /// ratios, clamped so shrinking the terminal never hides a pane. Below
+    /// both minimums combined the plain ratio is the best that can be done.
+    fn layout_sizes(&self, area: Rect) -> (u16, u16) {
+        let config = &self.context.config.config;
+        let percent = config
+            .sidebar_percent
+            .clamp(SIDEBAR_PERCENT_RANGE.0, SIDEBAR_PERCENT_RANGE.1);
+        let mut sidebar = (area.width as u32 * percent as u32 / 100) as u16;
+        if area.width >= MIN_SIDEBAR_WIDTH + MIN_DOCUMENTS_WIDTH {
+            sidebar = sidebar.clamp(MIN_SIDEBAR_WIDTH, area.width - MIN_DOCUMENTS_WIDTH);
+        }
+        let query_rows = config
+            .query_rows
+            .min(area.height.saturating_sub(MIN_RESULTS_ROWS))
+            .max(MIN_QUERY_ROWS);
+        (sidebar, query_rows)
+    }
+
+    /// Widens (`step` > 0) or narrows the sidebar and saves the new share.
+    fn resize_sidebar(&mut self, step: i16) {
+        let config = &mut self.context.config.config;
+        let percent = config.sidebar_percent as i16 + step * SIDEBAR_PERCENT_STEP as i16;
+        config.sidebar_percent =
+            (percent.max(0) as u16).clamp(SIDEBAR_PERCENT_RANGE.0, SIDEBAR_PERCENT_RANGE.1);
+        let percent = config.sidebar_percent;
+        match self.context.config.save() {
+            Ok(()) => self.context.set_status(format!("Sidebar {}%", percent)),
+            Err(e) => self.popup_state = PopupState::Error(format!("Failed to save config: {}", e)),
+        }
+    }
+
+    /// Selects the `--db`/`--collection` asked for on the command line and
+    /// loads its documents.
+    fn open_startup_namespace(&mut self, db_name: &str, coll_name: Option<&str>) {
+        match coll_name {
+            Some(coll_name) if self.context.select_collection_by_name(db_name, coll_name) => {
+                self.registry.set_active(self.doc_pane_id);
+                if let Some(tx) = &self.context.action_tx {
+                    let _ = tx.send(Action::RefreshDocuments);
+                }
+            }
+            Some(coll_name) => self
+                .context
+                .set_status(format!("Collection {}.{} not found", db_name, coll_name)),
+            None if !self.context.databases.iter().any(|db| db.name == db_name) => self
+                .context
+                .set_status(format!("Database {} not found", db_name)),
+            None => {}
+        }
+    }
+
+    /// Writes the config after a change to the saved queries, reporting
+    /// `done` in the footer or the error in a popup.
+    fn persist_saved_queries(&mut self, done: String) {
+        match self.context.config.save() {
+            Ok(()) => self.context.set_status(done),
+            Err(e) => self.popup_state = PopupState::Error(format!("Failed to save config: {}", e)),
+        }
+    }
+
+    fn saved_query(&self, name: &str) -> Option<&NamedQuery> {
+        self.context
+            .config
+            .config
+            .saved_queries
+            .iter()
+            .find(|q| q.name == name)
+    }
+
     fn get_global_shortcuts(&self) -> Vec<(&'static str, &'static str)> {
         vec![("q", "Quit"), ("?", "Help"), ("Tab", "Cycle")]
     }
 
     fn handle_popup_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        if let PopupState::ConfirmQuit(_) = self.popup_state {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => return Ok(Some(Action::Quit)),
+                KeyCode::Esc | KeyCode::Char('n') => {
+                    let popup = std::mem::replace(&mut self.popup_state, PopupState::None);
+                    if let PopupState::ConfirmQuit(previous) = popup {
+                        self.popup_state = *previous;
+                    }
+                    return Ok(Some(Action::Render));
+                }
+                _ => return Ok(None),
+            }
+        }
+
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            if !self.has_unsaved_edits {
+                return Ok(Some(Action::Quit));
+            }
+            let previous = std::mem::replace(&mut self.popup_state, PopupState::None);
+            self.popup_state = PopupState::ConfirmQuit(Box::new(previous));
+            return Ok(Some(Action::Render));
+        }
+
         match &mut self.popup_state {
+            PopupState::Confirm { on_confirm, .. } => match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    let action = std::mem::replace(on_confirm.as_mut(), Action::Render);
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(action));
+                }
+                KeyCode::Esc | KeyCode::Char('n') => {
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(Action::Render));
+                }
+                _ => return Ok(None),
+            },
             PopupState::Error(_) => {
                 if let KeyCode::Esc | KeyCode::Enter = key.code {
                     self.popup_state = PopupState::None;
@@ -111,54 +767,501 @@ impl MongoViewer {
             PopupState::ConnectionManager {
                 name,
                 uri,
-                is_editing_uri,
+                pool_size,
+                auth_source,
+                app_name,
+                tls,
+                active_field,
+                error,
+                editing,
+                test_result,
             } => match key.code {
+                KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    let u = uri.lines().join("").trim().to_string();
+                    if u.is_empty() {
+                        *error = Some("Enter a URI to test".into());
+                        return Ok(Some(Action::Render));
+                    }
+                    // Pool size doesn't change whether the server is reachable
+                    let options = mongo_core::ConnectOptions {
+                        max_pool_size: None,
+                        tls: *tls,
+                        auth_source: optional_text(auth_source),
+                        app_name: optional_text(app_name),
+                    };
+                    *error = None;
+                    *test_result = Some(Ok(None));
+                    return Ok(Some(Action::TestConnection(u, options)));
+                }
+                KeyCode::Char(' ') if *active_field == ConnectionField::Tls => {
+                    *tls = !*tls;
+                    self.has_unsaved_edits = true;
+                    return Ok(Some(Action::Render));
+                }
                 KeyCode::Esc => {
                     self.popup_state = PopupState::None;
                     return Ok(Some(Action::Render));
                 }
                 KeyCode::Tab => {
-                    *is_editing_uri = !*is_editing_uri;
+                    *active_field = active_field.next();
                     return Ok(Some(Action::Render));
                 }
                 KeyCode::Enter => {
                     let n = name.lines().join("");
                     let u = uri.lines().join("");
+                    let pool = pool_size.lines().join("");
+                    let max_pool_size = match pool.trim() {
+                        "" => None,
+                        text => match text.parse::<u32>() {
+                            Ok(size) if size > 0 => Some(size),
+                            _ => {
+                                *error = Some("Max pool size must be a positive number".into());
+                                return Ok(Some(Action::Render));
+                            }
+                        },
+                    };
+                    let n = n.trim().to_string();
+                    let taken = self
+                        .context
+                        .connections
+                        .iter()
+                        .enumerate()
+                        .any(|(i, c)| c.name == n && Some(i) != *editing);
+                    if taken {
+                        *error = Some(format!("A connection named '{}' already exists", n));
+                        return Ok(Some(Action::Render));
+                    }
                     if !n.is_empty() && !u.is_empty() {
+                        let mut connection = crate::config::Connection {
+                            name: n,
+                            uri: u,
+                            max_pool_size,
+                            tls: *tls,
+                            auth_source: optional_text(auth_source),
+                            app_name: optional_text(app_name),
+                            transient: false,
+                        };
+                        let action = match *editing {
+                            Some(idx) => {
+                                // An edited env connection stays out of the config file
+                                connection.transient = self
+                                    .context
+                                    .connections
+                                    .get(idx)
+                                    .is_some_and(|c| c.transient);
+                                Action::UpdateConnection(idx, connection)
+                            }
+                            None => Action::SaveConnection(connection),
+                        };
                         self.popup_state = PopupState::None;
-                        return Ok(Some(Action::SaveConnection(n, u)));
+                        return Ok(Some(action));
                     }
                 }
                 _ => {
-                    if *is_editing_uri {
-                        uri.input(key);
-                    } else {
-                        name.input(key);
-                    }
+                    let changed = match active_field {
+                        ConnectionField::Name => name.input(key),
+                        ConnectionField::Uri => uri.input(key),
+                        ConnectionField::PoolSize => pool_size.input(key),
+                        ConnectionField::AuthSource => auth_source.input(key),
+                        ConnectionField::AppName => app_name.input(key),
+                        ConnectionField::Tls => false,
+                    };
+                    self.has_unsaved_edits |= changed;
                     return Ok(Some(Action::Render));
                 }
             },
-            PopupState::JsonViewer(_, _, offset) => match key.code {
+            PopupState::CommandRunner {
+                db,
+                command,
+                is_editing_command,
+                error,
+                armed,
+            } => match key.code {
                 KeyCode::Esc => {
                     self.popup_state = PopupState::None;
                     return Ok(Some(Action::Render));
                 }
-                KeyCode::Down | KeyCode::Char('j') => {
-                    *offset = offset.saturating_add(1);
+                KeyCode::Tab => {
+                    *is_editing_command = !*is_editing_command;
                     return Ok(Some(Action::Render));
                 }
-                KeyCode::Up | KeyCode::Char('k') => {
-                    *offset = offset.saturating_sub(1);
+                KeyCode::Enter => {
+                    let mut db_name = db.lines().join("").trim().to_string();
+                    if db_name.is_empty() {
+                        // Same fallback as the placeholder shows
+                        db_name = "admin".to_string();
+                    }
+                    match util::parse_extended_json(&command.lines().join("\n")) {
+                        Ok(cmd) if !cmd.is_empty() => {
+                            // Drops and deletes are echoed back and need a
+                            // second Enter
+                            let echo = format!("{} on {}", cmd, db_name);
+                            if util::is_destructive_command(&cmd) && armed.as_ref() != Some(&echo) {
+                                *error = None;
+                                *armed = Some(echo);
+                                return Ok(Some(Action::Render));
+                            }
+                            self.popup_state = PopupState::None;
+                            return Ok(Some(Action::RunCommand(db_name, cmd)));
+                        }
+                        Ok(_) => *error = Some("Command is empty".to_string()),
+                        Err(e) => *error = Some(e.to_string()),
+                    }
+                    return Ok(Some(Action::Render));
+                }
+                _ => {
+                    let changed = if *is_editing_command {
+                        command.input(key)
+                    } else {
+                        db.input(key)
+                    };
+                    if changed {
+                        *armed = None;
+                    }
+                    self.has_unsaved_edits |= changed;
                     return Ok(Some(Action::Render));
                 }
-                _ => {}
             },
-            PopupState::Help(state) => match key.code {
-                KeyCode::Esc | KeyCode::Char('?') => {
+            PopupState::BulkUpdate {
+                filter,
+                update,
+                is_editing_update,
+                error,
+            } => match key.code {
+                KeyCode::Esc => {
                     self.popup_state = PopupState::None;
                     return Ok(Some(Action::Render));
                 }
-                KeyCode::Down | KeyCode::Char('j') => {
+                KeyCode::Tab => {
+                    *is_editing_update = !*is_editing_update;
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    let filter_text = filter.lines().join("\n");
+                    let parsed_filter = if filter_text.trim().is_empty() {
+                        Ok(mongo_core::bson::Document::new())
+                    } else {
+                        util::parse_extended_json(&filter_text)
+                            .map_err(|e| format!("Filter: {}", e))
+                    };
+                    let parsed_update = util::parse_extended_json(&update.lines().join("\n"))
+                        .and_then(|doc| util::check_update(&doc).map(|_| doc))
+                        .map_err(|e| format!("Update: {}", e));
+                    match (parsed_filter, parsed_update) {
+                        (Ok(filter), Ok(update)) => {
+                            let Some((db, coll)) = self
+                                .context
+                                .selected_namespace()
+                                .map(|(db, coll)| (db.to_string(), coll.to_string()))
+                            else {
+                                *error = Some("Select a collection first".to_string());
+                                return Ok(Some(Action::Render));
+                            };
+                            // The popup stays open until the count answers, so
+                            // a failed count keeps what was typed
+                            *error = None;
+                            return Ok(Some(Action::CountBulkUpdate(db, coll, filter, update)));
+                        }
+                        (Err(e), _) => {
+                            *is_editing_update = false;
+                            *error = Some(e);
+                        }
+                        (_, Err(e)) => {
+                            *is_editing_update = true;
+                            *error = Some(e);
+                        }
+                    }
+                    return Ok(Some(Action::Render));
+                }
+                _ => {
+                    let changed = if *is_editing_update {
+                        update.input(key)
+                    } else {
+                        filter.input(key)
+                    };
+                    self.has_unsaved_edits |= changed;
+                    return Ok(Some(Action::Render));
+                }
+            },
+            PopupState::ConfirmUpdateAll {
+                db,
+                coll,
+                update,
+                input,
+                error,
+                ..
+            } => match key.code {
+                KeyCode::Esc => {
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Enter => {
+                    if input.lines().join("") != UPDATE_ALL_PHRASE {
+                        *error = Some(format!("Type \"{}\" exactly to confirm", UPDATE_ALL_PHRASE));
+                        return Ok(Some(Action::Render));
+                    }
+                    let action = Action::UpdateMany(
+                        std::mem::take(db),
+                        std::mem::take(coll),
+                        mongo_core::bson::Document::new(),
+                        std::mem::take(update),
+                    );
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(action));
+                }
+                _ => {
+                    input.input(key);
+                    *error = None;
+                    return Ok(Some(Action::Render));
+                }
+            },
+            PopupState::InsertDocument { input, error } => match key.code {
+                KeyCode::Esc => {
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    let json = input.lines().join("\n");
+                    match util::parse_extended_json(&json) {
+                        Ok(_) => {
+                            self.popup_state = PopupState::None;
+                            return Ok(Some(Action::InsertDocument(json)));
+                        }
+                        Err(e) => *error = Some(e.to_string()),
+                    }
+                    return Ok(Some(Action::Render));
+                }
+                _ => {
+                    self.has_unsaved_edits |= input.input(key);
+                    return Ok(Some(Action::Render));
+                }
+            },
+            PopupState::ConfirmDrop {
+                db,
+                collection,
+                input,
+                error,
+            } => match key.code {
+                KeyCode::Esc => {
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Enter => {
+                    let expected = collection.as_ref().unwrap_or(db);
+                    if input.lines().join("") != *expected {
+                        *error = Some(format!("Type \"{}\" exactly to confirm", expected));
+                        return Ok(Some(Action::Render));
+                    }
+                    let action = match collection.take() {
+                        Some(coll) => Action::DropCollection(std::mem::take(db), coll),
+                        None => Action::DropDatabase(std::mem::take(db)),
+                    };
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(action));
+                }
+                _ => {
+                    input.input(key);
+                    *error = None;
+                    return Ok(Some(Action::Render));
+                }
+            },
+            PopupState::Prompt { kind, input, error } => match key.code {
+                KeyCode::Esc => {
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Enter => {
+                    let text = input.lines().join("");
+                    match *kind {
+                        PromptKind::JumpToRow => match text.trim().parse::<usize>() {
+                            Ok(row) if row > 0 => {
+                                self.popup_state = PopupState::None;
+                                return Ok(Some(Action::JumpToRow(row - 1)));
+                            }
+                            _ => *error = Some("Enter a row number (1 or more)".to_string()),
+                        },
+                        PromptKind::GoToPage => match text.trim().parse::<usize>() {
+                            Ok(page) if page > 0 => {
+                                self.popup_state = PopupState::None;
+                                return Ok(Some(Action::GoToPage(page - 1)));
+                            }
+                            _ => *error = Some("Enter a page number (1 or more)".to_string()),
+                        },
+                        PromptKind::ExportPath => {
+                            if text.trim().is_empty() {
+                                *error = Some("Enter a file path".to_string());
+                            } else {
+                                let path = export::expand_path(text.trim());
+                                let format = ExportFormat::for_path(&path, &self.export_columns);
+                                self.popup_state = PopupState::None;
+                                return Ok(Some(Action::ExportDocuments(path, format)));
+                            }
+                        }
+                        PromptKind::SaveQuery => {
+                            let name = text.trim().to_string();
+                            if name.is_empty() {
+                                *error = Some("Enter a name".to_string());
+                            } else if self.saved_query(&name).is_some() {
+                                self.popup_state = PopupState::Confirm {
+                                    message: format!("Overwrite saved query \"{}\"?", name),
+                                    on_confirm: Box::new(Action::SaveQuery(name)),
+                                };
+                            } else {
+                                self.popup_state = PopupState::None;
+                                return Ok(Some(Action::SaveQuery(name)));
+                            }
+                        }
+                    }
+                    return Ok(Some(Action::Render));
+                }
+                _ => {
+                    input.input(key);
+                    return Ok(Some(Action::Render));
+                }
+            },
+            PopupState::JsonViewer(_, _, _, _, Some(edit)) => match key.code {
+                KeyCode::Esc => {
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    // With a projection the editor only holds some fields, so
+                    // they are $set/$unset instead of replacing the document
+                    let partial = self.context.build_find_options().projection.is_some();
+                    let change = util::parse_extended_json(&edit.input.lines().join("\n"))
+                        .map_err(|e| e.to_string())
+                        .and_then(|edited| document_change(&edit.original, edited, partial));
+                    match change {
+                        Ok(change) if change.is_empty() => {
+                            self.popup_state = PopupState::None;
+                            self.context.set_status("No changes");
+                        }
+                        Ok(change) => {
+                            let filter = doc! { "_id": edit.original.get("_id").cloned() };
+                            self.popup_state = PopupState::None;
+                            return Ok(Some(Action::SaveDocumentEdit(filter, change)));
+                        }
+                        Err(e) => edit.error = Some(e),
+                    }
+                    return Ok(Some(Action::Render));
+                }
+                _ => {
+                    self.has_unsaved_edits |= edit.input.input(key);
+                    return Ok(Some(Action::Render));
+                }
+            },
+            PopupState::JsonViewer(json, _, offset, selection, None) => match key.code {
+                KeyCode::Esc if selection.is_some() => {
+                    *selection = None;
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Esc => {
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Char('V') => {
+                    *selection = match selection {
+                        Some(_) => None,
+                        None => Some((*offset, *offset)),
+                    };
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Char('y') => {
+                    let Some((anchor, cursor)) = selection.take() else {
+                        return Ok(None);
+                    };
+                    let (start, end) = (anchor.min(cursor), anchor.max(cursor));
+                    let text = json
+                        .lines()
+                        .skip(start)
+                        .take(end - start + 1)
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    self.context
+                        .copy(text, &format!("{} lines", end - start + 1));
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    if let Some((_, cursor)) = selection {
+                        let last = json.lines().count().saturating_sub(1);
+                        *cursor = (*cursor + 1).min(last);
+                    } else {
+                        *offset = offset.saturating_add(1);
+                    }
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    if let Some((_, cursor)) = selection {
+                        *cursor = cursor.saturating_sub(1);
+                        *offset = (*offset).min(*cursor);
+                    } else {
+                        *offset = offset.saturating_sub(1);
+                    }
+                    return Ok(Some(Action::Render));
+                }
+                _ => {}
+            },
+            PopupState::JsonTree(tree) => match key.code {
+                KeyCode::Esc => {
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    tree.move_cursor(1);
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    tree.move_cursor(-1);
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Enter | KeyCode::Char(' ') => {
+                    tree.toggle();
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Right | KeyCode::Char('l') => {
+                    tree.expand();
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Left | KeyCode::Char('h') => {
+                    tree.collapse();
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Char('y') => {
+                    let Some((key, value)) = tree.focused() else {
+                        return Ok(None);
+                    };
+                    let text = match value {
+                        Bson::Document(_) | Bson::Array(_) => {
+                            serde_json::to_string_pretty(&value.into_relaxed_extjson())
+                                .unwrap_or_default()
+                        }
+                        other => format_bson_copy(&other),
+                    };
+                    self.context.copy(text, &key);
+                    return Ok(Some(Action::Render));
+                }
+                _ => {}
+            },
+            PopupState::Help(state) => match key.code {
+                KeyCode::Esc | KeyCode::Char('?') => {
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Char('e') => {
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(Action::EditConfig));
+                }
+                KeyCode::Char('w') => {
+                    match self.export_shortcuts() {
+                        Ok(path) => self.context.set_status(format!("Wrote {}", path.display())),
+                        Err(e) => {
+                            self.popup_state =
+                                PopupState::Error(format!("Failed to write shortcuts: {}", e));
+                        }
+                    }
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
                     let i = match state.selected() {
                         Some(i) => i + 1,
                         None => 0,
@@ -182,8 +1285,177 @@ impl MongoViewer {
                 }
                 _ => {}
             },
-            PopupState::QueryBuilder { active_field } => {
+            PopupState::QueryBuilder {
+                active_field,
+                sort_picker,
+                filter_builder,
+                original_filter,
+            } => {
+                if let Some(builder) = filter_builder {
+                    let Some(row) = builder.rows.get_mut(builder.selected) else {
+                        *filter_builder = None;
+                        return Ok(Some(Action::Render));
+                    };
+                    let field_count = builder.fields.len();
+                    match key.code {
+                        KeyCode::Esc => *filter_builder = None,
+                        KeyCode::Tab => {
+                            builder.column = match builder.column {
+                                FilterColumn::Field => FilterColumn::Operator,
+                                FilterColumn::Operator => FilterColumn::Value,
+                                FilterColumn::Value => FilterColumn::Field,
+                            };
+                        }
+                        KeyCode::BackTab => {
+                            builder.column = match builder.column {
+                                FilterColumn::Field => FilterColumn::Value,
+                                FilterColumn::Operator => FilterColumn::Field,
+                                FilterColumn::Value => FilterColumn::Operator,
+                            };
+                        }
+                        KeyCode::Down => {
+                            builder.selected = (builder.selected + 1).min(builder.rows.len() - 1);
+                        }
+                        KeyCode::Up => builder.selected = builder.selected.saturating_sub(1),
+                        KeyCode::Left | KeyCode::Right => {
+                            let step = if key.code == KeyCode::Left { -1 } else { 1 };
+                            match builder.column {
+                                FilterColumn::Field => {
+                                    row.field = (row.field as isize + step)
+                                        .rem_euclid(field_count as isize)
+                                        as usize;
+                                }
+                                FilterColumn::Operator => row.op = row.op.cycle(step),
+                                FilterColumn::Value => {}
+                            }
+                        }
+                        KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            let field = row.field;
+                            builder.rows.push(FilterRow {
+                                field,
+                                op: FilterOp::Eq,
+                                value: String::new(),
+                            });
+                            builder.selected = builder.rows.len() - 1;
+                            builder.column = FilterColumn::Field;
+                        }
+                        KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            if builder.rows.len() > 1 {
+                                builder.rows.remove(builder.selected);
+                                builder.selected = builder.selected.min(builder.rows.len() - 1);
+                            } else {
+                                builder.rows[0].value.clear();
+                            }
+                        }
+                        KeyCode::Enter => {
+                            // Errors already show under the rows as they are typed
+                            if let Ok(filter) = util::compile_filter(&builder.fields, &builder.rows)
+                            {
+                                let filter =
+                                    Bson::Document(filter).into_relaxed_extjson().to_string();
+                                self.context.set_filter(&filter);
+                                self.context
+                                    .input_validation_errors
+                                    .remove(&QueryField::Filter);
+                                *filter_builder = None;
+                            }
+                        }
+                        KeyCode::Backspace if builder.column == FilterColumn::Value => {
+                            row.value.pop();
+                        }
+                        KeyCode::Char(c) if builder.column == FilterColumn::Value => {
+                            row.value.push(c);
+                        }
+                        _ => {}
+                    }
+                    return Ok(Some(Action::Render));
+                }
+
+                if let Some(picker) = sort_picker {
+                    match key.code {
+                        KeyCode::Esc => *sort_picker = None,
+                        KeyCode::Down | KeyCode::Char('j') => {
+                            let last = picker.fields.len().saturating_sub(1);
+                            let i = picker.state.selected().map_or(0, |i| (i + 1).min(last));
+                            picker.state.select(Some(i));
+                        }
+                        KeyCode::Up | KeyCode::Char('k') => {
+                            let i = picker.state.selected().map_or(0, |i| i.saturating_sub(1));
+                            picker.state.select(Some(i));
+                        }
+                        KeyCode::Char('d') | KeyCode::Left | KeyCode::Right => {
+                            picker.ascending = !picker.ascending;
+                        }
+                        KeyCode::Enter | KeyCode::Char(' ') => {
+                            if let Some(field) =
+                                picker.state.selected().and_then(|i| picker.fields.get(i))
+                            {
+                                let direction = if picker.ascending { 1 } else { -1 };
+                                picker.sort.insert(field.clone(), direction);
+                                let sort = Bson::Document(picker.sort.clone())
+                                    .into_relaxed_extjson()
+                                    .to_string();
+                                self.context.set_sort(&sort);
+                            }
+                        }
+                        KeyCode::Backspace => {
+                            if let Some(last) = picker.sort.keys().last().cloned() {
+                                picker.sort.remove(&last);
+                            }
+                            let sort = if picker.sort.is_empty() {
+                                String::new()
+                            } else {
+                                Bson::Document(picker.sort.clone())
+                                    .into_relaxed_extjson()
+                                    .to_string()
+                            };
+                            self.context.set_sort(&sort);
+                        }
+                        _ => {}
+                    }
+                    return Ok(Some(Action::Render));
+                }
+
                 match key.code {
+                    KeyCode::Char('g')
+                        if key.modifiers.contains(KeyModifiers::CONTROL)
+                            && *active_field == QueryField::Filter =>
+                    {
+                        let fields = self.context.filter_fields();
+                        if fields.is_empty() {
+                            self.context
+                                .set_status("No fields known yet: load some documents first");
+                            return Ok(Some(Action::Render));
+                        }
+                        *filter_builder = Some(FilterBuilder {
+                            fields,
+                            rows: vec![FilterRow {
+                                field: 0,
+                                op: FilterOp::Eq,
+                                value: String::new(),
+                            }],
+                            selected: 0,
+                            column: FilterColumn::Field,
+                        });
+                        return Ok(Some(Action::Render));
+                    }
+                    KeyCode::Char('g')
+                        if key.modifiers.contains(KeyModifiers::CONTROL)
+                            && *active_field == QueryField::Sort =>
+                    {
+                        let sort =
+                            util::parse_extended_json(&self.context.sort_input.lines().join("\n"))
+                                .unwrap_or_default();
+                        let mut state = ListState::default();
+                        state.select(Some(0));
+                        *sort_picker = Some(SortPicker {
+                            fields: self.context.loaded_fields(),
+                            state,
+                            ascending: true,
+                            sort,
+                        });
+                        return Ok(Some(Action::Render));
+                    }
                     KeyCode::Esc => {
                         self.popup_state = PopupState::None;
                         self.context.input_validation_errors.clear();
@@ -194,14 +1466,42 @@ impl MongoViewer {
                             QueryField::Filter => QueryField::Sort,
                             QueryField::Sort => QueryField::Projection,
                             QueryField::Projection => QueryField::Limit,
-                            QueryField::Limit => QueryField::Filter,
+                            QueryField::Limit | QueryField::Pipeline => QueryField::Filter,
+                        };
+                        return Ok(Some(Action::Render));
+                    }
+                    KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        if !self.context.validate_query_inputs() {
+                            *active_field =
+                                self.context.first_invalid_field().unwrap_or(*active_field);
+                            return Ok(Some(Action::Render));
+                        }
+                        self.popup_state = PopupState::Prompt {
+                            kind: PromptKind::SaveQuery,
+                            input: Box::default(),
+                            error: None,
                         };
                         return Ok(Some(Action::Render));
                     }
+                    KeyCode::Char('x') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        self.popup_state = PopupState::None;
+                        self.context.reset_query();
+                        self.context.set_status("Query reset");
+                        return Ok(Some(Action::RefreshDocuments));
+                    }
                     KeyCode::Enter => {
-                        // Simplify validation: just trigger refresh
+                        if !self.context.validate_query_inputs() {
+                            *active_field =
+                                self.context.first_invalid_field().unwrap_or(*active_field);
+                            return Ok(Some(Action::Render));
+                        }
+                        // A new filter changes the result set, so start over at
+                        // page 1; sort/projection/limit tweaks keep the page
+                        let filter = self.context.query_input.lines().join("\n");
+                        if filter.trim() != original_filter.trim() {
+                            self.context.pagination.current_page = 0;
+                        }
                         self.popup_state = PopupState::None;
-                        self.context.pagination.current_page = 0; // Reset pagination
                         return Ok(Some(Action::RefreshDocuments));
                     }
                     _ => {
@@ -218,136 +1518,967 @@ impl MongoViewer {
                             QueryField::Limit => {
                                 self.context.limit_input.input(key);
                             }
+                            QueryField::Pipeline => {
+                                self.context.pipeline_input.input(key);
+                            }
                         }
                         return Ok(Some(Action::Render));
                     }
                 }
             }
-            PopupState::FieldSelector(state, all_fields, visible_fields) => {
+            PopupState::SavedQueries(state) => {
+                let saved = &self.context.config.config.saved_queries;
                 match key.code {
                     KeyCode::Esc => {
                         self.popup_state = PopupState::None;
                         return Ok(Some(Action::Render));
                     }
                     KeyCode::Down | KeyCode::Char('j') => {
-                        let i = match state.selected() {
-                            Some(i) => {
-                                if i >= all_fields.len().saturating_sub(1) {
-                                    all_fields.len().saturating_sub(1)
-                                } else {
-                                    i + 1
-                                }
-                            }
-                            None => 0,
-                        };
+                        let last = saved.len().saturating_sub(1);
+                        let i = state.selected().map_or(0, |i| (i + 1).min(last));
                         state.select(Some(i));
                         return Ok(Some(Action::Render));
                     }
                     KeyCode::Up | KeyCode::Char('k') => {
-                        let i = match state.selected() {
-                            Some(i) => {
-                                if i == 0 {
-                                    0
-                                } else {
-                                    i - 1
-                                }
-                            }
-                            None => 0,
-                        };
+                        let i = state.selected().map_or(0, |i| i.saturating_sub(1));
                         state.select(Some(i));
                         return Ok(Some(Action::Render));
                     }
-                    KeyCode::Enter | KeyCode::Char(' ') => {
-                        if let Some(i) = state.selected() {
-                            if let Some(field) = all_fields.get(i) {
-                                // Clone visible_fields to modify
-                                let mut new_visible = visible_fields.clone();
-                                if new_visible.contains(field) {
-                                    new_visible.retain(|f| f != field);
-                                } else {
-                                    new_visible.push(field.clone());
-                                }
-
-                                // Update the popup state with the new visible fields
-                                *visible_fields = new_visible.clone();
-
-                                // Dispatch action to update the main view
-                                return Ok(Some(Action::UpdateVisibleFields(new_visible)));
-                            }
-                        }
+                    KeyCode::Enter => {
+                        let Some(query) = state.selected().and_then(|i| saved.get(i)) else {
+                            return Ok(None);
+                        };
+                        let query = QueryState::from(query);
+                        self.context.load_query(&query);
+                        self.context.pagination.current_page = 0;
+                        self.popup_state = PopupState::None;
+                        return Ok(Some(Action::RefreshDocuments));
+                    }
+                    KeyCode::Char('d') => {
+                        let Some(query) = state.selected().and_then(|i| saved.get(i)) else {
+                            return Ok(None);
+                        };
+                        self.popup_state = PopupState::Confirm {
+                            message: format!("Delete saved query \"{}\"?", query.name),
+                            on_confirm: Box::new(Action::DeleteSavedQuery(query.name.clone())),
+                        };
                         return Ok(Some(Action::Render));
                     }
                     _ => {}
                 }
-                return Ok(Some(Action::Render));
             }
-            _ => {}
-        }
-        Ok(None)
-    }
-
-    // Popup Drawing Methods
-    fn draw_error_popup(&self, f: &mut Frame, area: Rect, msg: &str) {
-        let block = Block::default()
-            .title("Error")
+            PopupState::QueryHistory(state) => match key.code {
+                KeyCode::Esc => {
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    let last = self.context.query_history.len().saturating_sub(1);
+                    let i = state.selected().map_or(0, |i| (i + 1).min(last));
+                    state.select(Some(i));
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    let i = state.selected().map_or(0, |i| i.saturating_sub(1));
+                    state.select(Some(i));
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Enter => {
+                    // The list shows the newest query first
+                    let Some(query) = state
+                        .selected()
+                        .and_then(|i| self.context.query_history.iter().rev().nth(i).cloned())
+                    else {
+                        return Ok(None);
+                    };
+                    self.context.load_query(&query);
+                    self.context.pagination.current_page = 0;
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(Action::RefreshDocuments));
+                }
+                _ => {}
+            },
+            PopupState::DistinctValues {
+                field,
+                values,
+                state,
+                ..
+            } => match key.code {
+                KeyCode::Esc => {
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    let last = values.len().saturating_sub(1);
+                    let i = state.selected().map_or(0, |i| (i + 1).min(last));
+                    state.select(Some(i));
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    let i = state.selected().map_or(0, |i| i.saturating_sub(1));
+                    state.select(Some(i));
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Enter => {
+                    let Some(value) = state.selected().and_then(|i| values.get(i)) else {
+                        return Ok(None);
+                    };
+                    // Added to whatever the filter already holds
+                    let current = self.context.query_input.lines().join("\n");
+                    let mut filter = if current.trim().is_empty() {
+                        mongo_core::bson::Document::new()
+                    } else {
+                        match util::parse_extended_json(&current) {
+                            Ok(filter) => filter,
+                            Err(e) => {
+                                self.popup_state =
+                                    PopupState::Error(format!("Current filter: {}", e));
+                                return Ok(Some(Action::Render));
+                            }
+                        }
+                    };
+                    filter.insert(field.clone(), value.clone());
+                    let filter = Bson::Document(filter).into_relaxed_extjson().to_string();
+                    self.context.set_filter(&filter);
+                    self.context.pagination.current_page = 0;
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(Action::RefreshDocuments));
+                }
+                _ => {}
+            },
+            PopupState::Indexes(state, indexes) => match key.code {
+                KeyCode::Esc | KeyCode::Char('i') => {
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    let last = indexes.len().saturating_sub(1);
+                    let i = state.selected().map_or(0, |i| (i + 1).min(last));
+                    state.select(Some(i));
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    let i = state.selected().map_or(0, |i| i.saturating_sub(1));
+                    state.select(Some(i));
+                    return Ok(Some(Action::Render));
+                }
+                _ => {}
+            },
+            PopupState::CollectionStats(..) => {
+                if matches!(key.code, KeyCode::Esc | KeyCode::Char('s')) {
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(Action::Render));
+                }
+            }
+            PopupState::Explain { raw, offset, .. } => match key.code {
+                KeyCode::Esc | KeyCode::Char('x') => {
+                    self.popup_state = PopupState::None;
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Char('r') => {
+                    *raw = !*raw;
+                    *offset = 0;
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Down | KeyCode::Char('j') if *raw => {
+                    *offset = offset.saturating_add(1);
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Up | KeyCode::Char('k') if *raw => {
+                    *offset = offset.saturating_sub(1);
+                    return Ok(Some(Action::Render));
+                }
+                _ => {}
+            },
+            PopupState::AggregationBuilder => match key.code {
+                KeyCode::Esc => {
+                    self.popup_state = PopupState::None;
+                    self.context.input_validation_errors.clear();
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Enter => {
+                    match parse_pipeline(&self.context.pipeline_input.lines().join("\n")) {
+                        Ok(pipeline) => {
+                            self.popup_state = PopupState::None;
+                            self.context.input_validation_errors.clear();
+                            return Ok(Some(Action::RunAggregation(pipeline)));
+                        }
+                        Err(e) => {
+                            self.context
+                                .input_validation_errors
+                                .insert(QueryField::Pipeline, e);
+                        }
+                    }
+                    return Ok(Some(Action::Render));
+                }
+                _ => {
+                    self.context.pipeline_input.input(key);
+                    self.context
+                        .input_validation_errors
+                        .remove(&QueryField::Pipeline);
+                    return Ok(Some(Action::Render));
+                }
+            },
+            PopupState::FieldSelector(state, all_fields, visible_fields) => {
+                match key.code {
+                    KeyCode::Esc => {
+                        self.popup_state = PopupState::None;
+                        return Ok(Some(Action::Render));
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        let i = match state.selected() {
+                            Some(i) => {
+                                if i >= all_fields.len().saturating_sub(1) {
+                                    all_fields.len().saturating_sub(1)
+                                } else {
+                                    i + 1
+                                }
+                            }
+                            None => 0,
+                        };
+                        state.select(Some(i));
+                        return Ok(Some(Action::Render));
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        let i = match state.selected() {
+                            Some(i) => {
+                                if i == 0 {
+                                    0
+                                } else {
+                                    i - 1
+                                }
+                            }
+                            None => 0,
+                        };
+                        state.select(Some(i));
+                        return Ok(Some(Action::Render));
+                    }
+                    KeyCode::Enter | KeyCode::Char(' ') => {
+                        if let Some(i) = state.selected() {
+                            if let Some(field) = all_fields.get(i) {
+                                // Clone visible_fields to modify
+                                let mut new_visible = visible_fields.clone();
+                                if new_visible.contains(field) {
+                                    new_visible.retain(|f| f != field);
+                                    // A table without columns looks broken
+                                    if new_visible.is_empty() {
+                                        self.context
+                                            .set_status("At least one column must stay visible");
+                                        return Ok(Some(Action::Render));
+                                    }
+                                } else {
+                                    new_visible.push(field.clone());
+                                }
+
+                                // Update the popup state with the new visible fields
+                                *visible_fields = new_visible.clone();
+
+                                // Dispatch action to update the main view
+                                return Ok(Some(Action::UpdateVisibleFields(new_visible)));
+                            }
+                        }
+                        return Ok(Some(Action::Render));
+                    }
+                    _ => {}
+                }
+                return Ok(Some(Action::Render));
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    // Popup Drawing Methods
+    fn draw_popup(&self, f: &mut Frame, area: Rect, popup: &mut PopupState) {
+        match popup {
+            PopupState::ConnectionManager { .. } => {
+                self.draw_connection_manager_popup(f, area, popup)
+            }
+            PopupState::CommandRunner { .. } => self.draw_command_runner_popup(f, area, popup),
+            PopupState::QueryBuilder {
+                active_field,
+                sort_picker,
+                filter_builder,
+                ..
+            } => {
+                self.draw_query_builder_popup(f, area, active_field);
+                if let Some(picker) = sort_picker {
+                    self.draw_sort_picker_popup(f, area, picker);
+                }
+                if let Some(builder) = filter_builder {
+                    self.draw_filter_builder_popup(f, area, builder);
+                }
+            }
+            PopupState::InsertDocument { input, error } => {
+                self.draw_insert_document_popup(f, area, input, error.as_deref())
+            }
+            PopupState::Prompt { kind, input, error } => {
+                self.draw_prompt_popup(f, area, *kind, input, error.as_deref())
+            }
+            PopupState::DistinctValues {
+                field,
+                values,
+                state,
+                truncated,
+            } => self.draw_distinct_popup(f, area, field, values, state, *truncated),
+            PopupState::Indexes(state, indexes) => self.draw_indexes_popup(f, area, state, indexes),
+            PopupState::CollectionStats(namespace, stats) => {
+                self.draw_collection_stats_popup(f, area, namespace, stats)
+            }
+            PopupState::Explain {
+                explain,
+                raw,
+                offset,
+            } => self.draw_explain_popup(f, area, explain, *raw, *offset),
+            PopupState::ConfirmDrop {
+                db,
+                collection,
+                input,
+                error,
+            } => self.draw_confirm_drop_popup(
+                f,
+                area,
+                db,
+                collection.as_deref(),
+                input,
+                error.as_deref(),
+            ),
+            PopupState::BulkUpdate { .. } => self.draw_bulk_update_popup(f, area, popup),
+            PopupState::ConfirmUpdateAll {
+                count,
+                input,
+                error,
+                ..
+            } => self.draw_confirm_update_all_popup(f, area, *count, input, error.as_deref()),
+            PopupState::QueryHistory(state) => self.draw_query_history_popup(f, area, state),
+            PopupState::SavedQueries(state) => self.draw_saved_queries_popup(f, area, state),
+            PopupState::AggregationBuilder => self.draw_aggregation_builder_popup(f, area),
+            PopupState::JsonViewer(_, title, _, _, Some(edit)) => {
+                self.draw_document_edit_popup(f, area, title, edit)
+            }
+            PopupState::JsonViewer(json, title, offset, selection, None) => {
+                self.draw_json_popup(f, area, json, title, offset, *selection)
+            }
+            PopupState::JsonTree(tree) => self.draw_json_tree_popup(f, area, tree),
+            PopupState::Help(state) => self.draw_help_popup(f, area, state),
+            PopupState::Error(msg) => self.draw_error_popup(f, area, msg),
+            PopupState::FieldSelector(state, all_fields, visible_fields) => {
+                self.draw_field_selector_popup(f, area, state, all_fields, visible_fields)
+            }
+            PopupState::Confirm { message, .. } => self.draw_confirm_popup(f, area, message),
+            PopupState::ConfirmQuit(previous) => {
+                self.draw_popup(f, area, previous);
+                self.draw_confirm_quit_popup(f, area);
+            }
+            _ => {}
+        }
+    }
+
+    fn draw_confirm_quit_popup(&self, f: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .title("Unsaved Changes")
+            .borders(Borders::ALL)
+            .style(self.context.theme.border(true));
+        let paragraph = Paragraph::new(vec![
+            Line::from("You have unsaved edits. Quit anyway?"),
+            Line::from(""),
+            Line::from("y/Enter: Quit | Esc: Back to editing"),
+        ])
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+        let area = centered_rect(50, 20, area);
+        f.render_widget(Clear, area);
+        f.render_widget(paragraph, area);
+    }
+
+    fn draw_confirm_popup(&self, f: &mut Frame, area: Rect, message: &str) {
+        let block = Block::default()
+            .title("Confirm")
+            .borders(Borders::ALL)
+            .style(self.context.theme.border(true));
+        let paragraph = Paragraph::new(vec![
+            Line::from(message),
+            Line::from(""),
+            Line::from("y/Enter: Confirm | Esc: Cancel"),
+        ])
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+        let area = centered_rect(50, 20, area);
+        f.render_widget(Clear, area);
+        f.render_widget(paragraph, area);
+    }
+
+    fn draw_error_popup(&self, f: &mut Frame, area: Rect, msg: &str) {
+        let block = Block::default()
+            .title("Error")
             .borders(Borders::ALL)
-            .style(Style::default().fg(Color::Red));
+            .style(self.context.theme.error());
         let paragraph = Paragraph::new(msg).block(block).wrap(Wrap { trim: true });
         let area = centered_rect(60, 20, area);
         f.render_widget(Clear, area);
         f.render_widget(paragraph, area);
     }
 
-    fn draw_connection_manager_popup(
+    fn draw_connection_manager_popup(&self, f: &mut Frame, area: Rect, popup: &PopupState) {
+        let PopupState::ConnectionManager {
+            name,
+            uri,
+            pool_size,
+            auth_source,
+            app_name,
+            tls,
+            active_field,
+            error,
+            editing,
+            test_result,
+        } = popup
+        else {
+            return;
+        };
+        let (tls, active_field) = (*tls, *active_field);
+        let status = match (error, test_result) {
+            (Some(error), _) => Some(Line::from(error.as_str()).style(self.context.theme.error())),
+            (None, Some(Ok(None))) => {
+                Some(Line::from("Testing…").style(self.context.theme.muted()))
+            }
+            (None, Some(Ok(Some(ms)))) => Some(
+                Line::from(format!("Reachable ({} ms)", ms)).style(self.context.theme.success()),
+            ),
+            (None, Some(Err(e))) => Some(Line::from(e.as_str()).style(self.context.theme.error())),
+            (None, None) => None,
+        };
+        let area = centered_rect(60, 60, area);
+        f.render_widget(Clear, area);
+        let block = Block::default()
+            .title(if editing.is_some() {
+                "Edit Connection"
+            } else {
+                "New Connection"
+            })
+            .borders(Borders::ALL);
+        f.render_widget(block.clone(), area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(2)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(1),
+            ])
+            .split(area);
+        // Optional settings share one row
+        let options = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 3); 3])
+            .split(chunks[2]);
+
+        let fields = [
+            (name, "Name", ConnectionField::Name, chunks[0]),
+            (uri, "URI", ConnectionField::Uri, chunks[1]),
+            (
+                pool_size,
+                "Max Pool Size",
+                ConnectionField::PoolSize,
+                options[0],
+            ),
+            (
+                auth_source,
+                "Auth Source",
+                ConnectionField::AuthSource,
+                options[1],
+            ),
+            (app_name, "App Name", ConnectionField::AppName, options[2]),
+        ];
+        for (input, title, field, chunk) in fields {
+            let style = if active_field == field {
+                self.context.theme.border(true)
+            } else {
+                Style::default()
+            };
+            let block = Block::default().borders(Borders::ALL).title(title);
+            // The password only shows while the URI is being edited
+            if field == ConnectionField::Uri && active_field != field && !input.is_empty() {
+                let text = mongo_core::redact_uri(&input.lines().join(""));
+                f.render_widget(Paragraph::new(text).block(block), chunk);
+                continue;
+            }
+            let mut widget = TextArea::clone(input);
+            widget.set_block(block);
+            widget.set_style(style);
+            f.render_widget(&widget, chunk);
+        }
+
+        let tls_line = Line::from(format!(
+            " [{}] TLS (Space to toggle)",
+            if tls { "x" } else { " " }
+        ));
+        let tls_line = if active_field == ConnectionField::Tls {
+            tls_line.style(self.context.theme.accent())
+        } else {
+            tls_line
+        };
+        f.render_widget(Paragraph::new(tls_line), chunks[3]);
+
+        if let Some(status) = status {
+            f.render_widget(Paragraph::new(status), chunks[4]);
+        }
+
+        let help = Paragraph::new("Tab: Switch | Ctrl-t: Test | Enter: Save | Esc: Cancel")
+            .alignment(Alignment::Center);
+        f.render_widget(help, chunks[5]);
+    }
+
+    fn draw_prompt_popup(
+        &self,
+        f: &mut Frame,
+        area: Rect,
+        kind: PromptKind,
+        input: &TextArea,
+        error: Option<&str>,
+    ) {
+        let area = centered_rect(40, 20, area);
+        let area = Rect {
+            height: area.height.min(3),
+            ..area
+        };
+        f.render_widget(Clear, area);
+
+        let mut block = Block::default()
+            .title(kind.title())
+            .borders(Borders::ALL)
+            .border_style(self.context.theme.border(true));
+        if let Some(error) = error {
+            block = block.title_bottom(Line::from(error).style(self.context.theme.error()));
+        }
+
+        let mut widget = input.clone();
+        widget.set_block(block);
+        f.render_widget(&widget, area);
+    }
+
+    fn draw_confirm_drop_popup(
         &self,
         f: &mut Frame,
         area: Rect,
-        name: &TextArea,
-        uri: &TextArea,
-        is_editing_uri: bool,
+        db: &str,
+        collection: Option<&str>,
+        input: &TextArea,
+        error: Option<&str>,
     ) {
-        let area = centered_rect(60, 40, area);
+        let area = centered_rect(50, 30, area);
         f.render_widget(Clear, area);
+        let theme = &self.context.theme;
+        let (title, target, name) = match collection {
+            Some(coll) => (
+                "Drop Collection",
+                format!(
+                    "collection {}.{} with all its documents and indexes",
+                    db, coll
+                ),
+                coll,
+            ),
+            None => (
+                "Drop Database",
+                format!("database {} with all its collections", db),
+                db,
+            ),
+        };
         let block = Block::default()
-            .title("New Connection")
-            .borders(Borders::ALL);
-        f.render_widget(block.clone(), area);
+            .title(title)
+            .title_bottom(
+                Line::from(" Enter: Drop | Esc: Cancel ")
+                    .style(theme.muted())
+                    .alignment(Alignment::Right),
+            )
+            .borders(Borders::ALL)
+            .border_style(theme.error());
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let [message_area, input_area, error_area] = Layout::vertical([
+            Constraint::Min(2),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+        let message = Paragraph::new(vec![
+            Line::from(format!("This permanently deletes the {}.", target)),
+            Line::from(vec![
+                Span::raw("Type "),
+                Span::styled(name.to_string(), theme.accent()),
+                Span::raw(" to confirm."),
+            ]),
+        ])
+        .wrap(Wrap { trim: true });
+        f.render_widget(message, message_area);
+
+        let mut widget = input.clone();
+        widget.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(theme.border(true)),
+        );
+        f.render_widget(&widget, input_area);
+
+        if let Some(error) = error {
+            f.render_widget(Paragraph::new(error).style(theme.error()), error_area);
+        }
+    }
+
+    fn draw_bulk_update_popup(&self, f: &mut Frame, area: Rect, popup: &PopupState) {
+        let PopupState::BulkUpdate {
+            filter,
+            update,
+            is_editing_update,
+            error,
+        } = popup
+        else {
+            return;
+        };
+        let area = centered_rect(70, 60, area);
+        f.render_widget(Clear, area);
+        let title = match self.context.selected_namespace() {
+            Some((db, coll)) => format!("Update Many in {}.{}", db, coll),
+            None => "Update Many".to_string(),
+        };
+        let block = Block::default().title(title).borders(Borders::ALL);
+        f.render_widget(block, area);
+
+        let [filter_area, update_area, error_area, help_area] = Layout::vertical([
+            Constraint::Min(3),
+            Constraint::Min(3),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .margin(1)
+        .areas(area);
+
+        let active_style = self.context.theme.border(true);
+
+        let mut filter_widget = TextArea::clone(filter);
+        filter_widget.set_block(Block::default().borders(Borders::ALL).title("Filter"));
+        if !is_editing_update {
+            filter_widget.set_style(active_style);
+        }
+        f.render_widget(&filter_widget, filter_area);
+
+        let mut update_widget = TextArea::clone(update);
+        update_widget.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Update ($set, $inc, $unset, ...)"),
+        );
+        if *is_editing_update {
+            update_widget.set_style(active_style);
+        }
+        f.render_widget(&update_widget, update_area);
+
+        if let Some(error) = error {
+            let error = Paragraph::new(error.as_str()).style(self.context.theme.error());
+            f.render_widget(error, error_area);
+        }
+
+        let help = "Tab: Switch | Ctrl-s: Count Matches | Esc: Cancel";
+        f.render_widget(Paragraph::new(help).alignment(Alignment::Center), help_area);
+    }
+
+    fn draw_confirm_update_all_popup(
+        &self,
+        f: &mut Frame,
+        area: Rect,
+        count: u64,
+        input: &TextArea,
+        error: Option<&str>,
+    ) {
+        let area = centered_rect(50, 30, area);
+        f.render_widget(Clear, area);
+        let theme = &self.context.theme;
+        let block = Block::default()
+            .title("Update All Documents")
+            .title_bottom(
+                Line::from(" Enter: Update | Esc: Cancel ")
+                    .style(theme.muted())
+                    .alignment(Alignment::Right),
+            )
+            .borders(Borders::ALL)
+            .border_style(theme.error());
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let [message_area, input_area, error_area] = Layout::vertical([
+            Constraint::Min(2),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+        let message = Paragraph::new(vec![
+            Line::from(format!(
+                "The filter is empty: this updates all {} documents of the collection.",
+                format_count(count)
+            )),
+            Line::from(vec![
+                Span::raw("Type "),
+                Span::styled(UPDATE_ALL_PHRASE, theme.accent()),
+                Span::raw(" to confirm."),
+            ]),
+        ])
+        .wrap(Wrap { trim: true });
+        f.render_widget(message, message_area);
+
+        let mut widget = input.clone();
+        widget.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(theme.border(true)),
+        );
+        f.render_widget(&widget, input_area);
+
+        if let Some(error) = error {
+            f.render_widget(Paragraph::new(error).style(theme.error()), error_area);
+        }
+    }
+
+    fn draw_insert_document_popup(
+        &self,
+        f: &mut Frame,
+        area: Rect,
+        input: &TextArea,
+        error: Option<&str>,
+    ) {
+        let area = centered_rect(70, 60, area);
+        f.render_widget(Clear, area);
+        let title = match self.context.selected_namespace() {
+            Some((db, coll)) => format!("Insert Document: {}.{}", db, coll),
+            None => "Insert Document".to_string(),
+        };
+        let mut block = Block::default()
+            .title(title)
+            .title_bottom(
+                Line::from(" Ctrl-s: Insert | Esc: Cancel ")
+                    .style(self.context.theme.muted())
+                    .alignment(Alignment::Right),
+            )
+            .borders(Borders::ALL)
+            .border_style(self.context.theme.border(true));
+        if let Some(error) = error {
+            block = block.title_bottom(Line::from(error).style(self.context.theme.error()));
+        }
+
+        let mut widget = input.clone();
+        widget.set_block(block);
+        f.render_widget(&widget, area);
+    }
+
+    fn draw_document_edit_popup(
+        &self,
+        f: &mut Frame,
+        area: Rect,
+        title: &str,
+        edit: &DocumentEdit,
+    ) {
+        let area = centered_rect(80, 80, area);
+        f.render_widget(Clear, area);
+        let mut block = Block::default()
+            .title(format!("Edit Document: {}", title))
+            .title_bottom(
+                Line::from(" Ctrl-s: Save | Esc: Cancel ")
+                    .style(self.context.theme.muted())
+                    .alignment(Alignment::Right),
+            )
+            .borders(Borders::ALL)
+            .border_style(self.context.theme.border(true));
+        if let Some(error) = &edit.error {
+            block =
+                block.title_bottom(Line::from(error.as_str()).style(self.context.theme.error()));
+        }
+
+        let mut widget = (*edit.input).clone();
+        widget.set_block(block);
+        f.render_widget(&widget, area);
+    }
+
+    fn draw_command_runner_popup(&self, f: &mut Frame, area: Rect, popup: &PopupState) {
+        let PopupState::CommandRunner {
+            db,
+            command,
+            is_editing_command,
+            error,
+            armed,
+        } = popup
+        else {
+            return;
+        };
+        let is_editing_command = *is_editing_command;
+        let area = centered_rect(70, 60, area);
+        f.render_widget(Clear, area);
+        let block = Block::default().title("Run Command").borders(Borders::ALL);
+        f.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(3),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .split(area);
+
+        let active_style = self.context.theme.border(true);
+
+        let mut db_widget = TextArea::clone(db);
+        db_widget.set_block(Block::default().borders(Borders::ALL).title("Database"));
+        if !is_editing_command {
+            db_widget.set_style(active_style);
+        }
+        f.render_widget(&db_widget, chunks[0]);
+
+        let mut command_widget = TextArea::clone(command);
+        command_widget.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Command (JSON)"),
+        );
+        if is_editing_command {
+            command_widget.set_style(active_style);
+        }
+        f.render_widget(&command_widget, chunks[1]);
+
+        if let Some(echo) = armed {
+            let warning = Paragraph::new(format!("Destructive: {}", echo))
+                .style(self.context.theme.error().add_modifier(Modifier::BOLD));
+            f.render_widget(warning, chunks[2]);
+        } else if let Some(error) = error {
+            let error = Paragraph::new(error.as_str()).style(self.context.theme.error());
+            f.render_widget(error, chunks[2]);
+        }
+
+        let help = if armed.is_some() {
+            "Enter: Run It | Edit to Change | Esc: Cancel"
+        } else {
+            "Tab: Switch | Enter: Run | Esc: Cancel"
+        };
+        f.render_widget(Paragraph::new(help).alignment(Alignment::Center), chunks[3]);
+    }
+
+    fn draw_sort_picker_popup(&self, f: &mut Frame, area: Rect, picker: &mut SortPicker) {
+        let area = centered_rect(40, 50, area);
+        f.render_widget(Clear, area);
+        let direction = if picker.ascending {
+            "asc (1)"
+        } else {
+            "desc (-1)"
+        };
+        let sort = Bson::Document(picker.sort.clone())
+            .into_relaxed_extjson()
+            .to_string();
+        let block = Block::default()
+            .title(format!("Sort Fields - next: {}", direction))
+            .title_bottom(Line::from(sort).alignment(Alignment::Center))
+            .borders(Borders::ALL)
+            .border_style(self.context.theme.border(true));
+
+        let items: Vec<ListItem> = picker
+            .fields
+            .iter()
+            .map(|field| match picker.sort.get_i32(field) {
+                Ok(dir) => {
+                    ListItem::new(format!("{} ({})", field, dir)).style(self.context.theme.label())
+                }
+                Err(_) => ListItem::new(field.as_str()),
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(self.context.theme.highlight())
+            .highlight_symbol(self.context.theme.highlight_symbol());
+
+        f.render_stateful_widget(list, area, &mut picker.state);
+    }
+
+    fn draw_filter_builder_popup(&self, f: &mut Frame, area: Rect, builder: &FilterBuilder) {
+        let area = centered_rect(70, 50, area);
+        f.render_widget(Clear, area);
+        let theme = &self.context.theme;
+        let block = Block::default()
+            .title(format!("Filter Builder ({} rows, ANDed)", builder.rows.len()))
+            .title_bottom(
+                Line::from(
+                    " Tab: Column | Left/Right: Change | Ctrl-n: Add | Ctrl-d: Remove | Enter: Use | Esc: Back ",
+                )
+                .style(theme.muted())
+                .alignment(Alignment::Center),
+            )
+            .borders(Borders::ALL)
+            .border_style(theme.border(true));
+        let inner = block.inner(area);
+        f.render_widget(block, area);
 
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .margin(2)
-            .constraints([
-                Constraint::Length(3),
-                Constraint::Length(3),
-                Constraint::Min(1),
-            ])
-            .split(area);
+            .constraints([Constraint::Min(1), Constraint::Length(4)])
+            .split(inner);
 
-        let name_block = Block::default().borders(Borders::ALL).title("Name");
-        let name_style = if !is_editing_uri {
-            Style::default().fg(Color::Yellow)
-        } else {
-            Style::default()
+        let field_label = |row: &FilterRow| {
+            builder
+                .fields
+                .get(row.field)
+                .map(|(path, type_name)| format!("{} ({})", path, type_name))
+                .unwrap_or_default()
         };
-        let mut name_widget = name.clone();
-        name_widget.set_block(name_block);
-        name_widget.set_style(name_style);
-        f.render_widget(&name_widget, chunks[0]);
+        let field_width = builder
+            .rows
+            .iter()
+            .map(|row| field_label(row).chars().count())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<Line> = builder
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let cell = |column: FilterColumn, text: String| {
+                    let style = if i != builder.selected {
+                        Style::default()
+                    } else if column == builder.column {
+                        theme.highlight()
+                    } else {
+                        theme.selection()
+                    };
+                    Span::styled(text, style)
+                };
+                Line::from(vec![
+                    cell(
+                        FilterColumn::Field,
+                        format!("{:<width$}", field_label(row), width = field_width),
+                    ),
+                    Span::raw(" "),
+                    cell(FilterColumn::Operator, format!("{:^8}", row.op.label())),
+                    Span::raw(" "),
+                    cell(FilterColumn::Value, format!("{} ", row.value)),
+                ])
+            })
+            .collect();
+        f.render_widget(Paragraph::new(lines), chunks[0]);
 
-        let uri_block = Block::default().borders(Borders::ALL).title("URI");
-        let uri_style = if is_editing_uri {
-            Style::default().fg(Color::Yellow)
-        } else {
-            Style::default()
+        let preview = match util::compile_filter(&builder.fields, &builder.rows) {
+            Ok(filter) => Paragraph::new(Bson::Document(filter).into_relaxed_extjson().to_string()),
+            Err(e) => Paragraph::new(e.to_string()).style(theme.error()),
         };
-        let mut uri_widget = uri.clone();
-        uri_widget.set_block(uri_block);
-        uri_widget.set_style(uri_style);
-        f.render_widget(&uri_widget, chunks[1]);
-
-        let help =
-            Paragraph::new("Tab: Switch | Enter: Save | Esc: Cancel").alignment(Alignment::Center);
-        f.render_widget(help, chunks[2]);
+        f.render_widget(
+            preview
+                .block(Block::default().borders(Borders::TOP).title("Filter"))
+                .wrap(Wrap { trim: true }),
+            chunks[1],
+        );
     }
 
     fn draw_query_builder_popup(&self, f: &mut Frame, area: Rect, active_field: &QueryField) {
@@ -371,13 +2502,18 @@ impl MongoViewer {
             .split(area);
 
         let draw_input =
-            |f: &mut Frame, chunk: Rect, title: &str, input: &TextArea, is_active: bool| {
+            |f: &mut Frame, chunk: Rect, title: &str, input: &TextArea, field: QueryField| {
                 let mut widget = input.clone();
                 let mut block = Block::default().borders(Borders::ALL).title(title);
-                if is_active {
-                    block = block.border_style(Style::default().fg(Color::Yellow));
+                if *active_field == field {
+                    block = block.border_style(self.context.theme.border(true));
                     widget.set_cursor_style(Style::default().add_modifier(Modifier::REVERSED));
                 }
+                if let Some(error) = self.context.input_validation_errors.get(&field) {
+                    block = block
+                        .border_style(self.context.theme.error())
+                        .title_bottom(Line::from(error.as_str()).style(self.context.theme.error()));
+                }
                 widget.set_block(block);
                 f.render_widget(&widget, chunk);
             };
@@ -385,100 +2521,650 @@ impl MongoViewer {
         draw_input(
             f,
             chunks[0],
-            "Filter (JSON)",
+            "Filter (JSON, Ctrl-g: Guided)",
             &self.context.query_input,
-            *active_field == QueryField::Filter,
+            QueryField::Filter,
         );
         draw_input(
             f,
             chunks[1],
-            "Sort (JSON)",
+            "Sort (JSON, Ctrl-g: Pick Fields)",
             &self.context.sort_input,
-            *active_field == QueryField::Sort,
+            QueryField::Sort,
         );
         draw_input(
             f,
             chunks[2],
             "Projection (JSON)",
             &self.context.projection_input,
-            *active_field == QueryField::Projection,
+            QueryField::Projection,
         );
         draw_input(
             f,
             chunks[3],
             "Limit (Number)",
             &self.context.limit_input,
-            *active_field == QueryField::Limit,
+            QueryField::Limit,
         );
 
-        let help =
-            Paragraph::new("Tab: Cycle | Enter: Apply | Esc: Cancel").alignment(Alignment::Center);
+        let help = Paragraph::new(
+            "Tab: Cycle | Enter: Apply | Ctrl-s: Save As | Ctrl-x: Reset | Ctrl-g: Guided Filter/Sort | Esc: Cancel",
+        )
+        .alignment(Alignment::Center);
         f.render_widget(help, chunks[4]);
     }
 
-    fn draw_json_popup(&self, f: &mut Frame, area: Rect, json: &str, title: &str, offset: usize) {
-        let area = centered_rect(80, 80, area);
+    fn draw_distinct_popup(
+        &self,
+        f: &mut Frame,
+        area: Rect,
+        field: &str,
+        values: &[Bson],
+        state: &mut ListState,
+        truncated: bool,
+    ) {
+        let area = centered_rect(50, 60, area);
+        f.render_widget(Clear, area);
+        let mut block = Block::default()
+            .title(format!("Distinct: {} ({})", field, values.len()))
+            .title_bottom(
+                Line::from(" Enter: Add to Filter | Esc: Close ")
+                    .style(self.context.theme.muted())
+                    .alignment(Alignment::Right),
+            )
+            .borders(Borders::ALL);
+        if truncated {
+            block = block.title_bottom(
+                Line::from(format!(" first {} values only ", DISTINCT_LIMIT))
+                    .style(self.context.theme.error())
+                    .alignment(Alignment::Left),
+            );
+        }
+
+        let items: Vec<ListItem> = values
+            .iter()
+            .map(|value| ListItem::new(format_bson_cell(value)))
+            .collect();
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(self.context.theme.highlight())
+            .highlight_symbol(self.context.theme.highlight_symbol());
+
+        f.render_stateful_widget(list, area, state);
+    }
+
+    fn draw_query_history_popup(&self, f: &mut Frame, area: Rect, state: &mut ListState) {
+        let area = centered_rect(70, 60, area);
         f.render_widget(Clear, area);
+        let theme = &self.context.theme;
         let block = Block::default()
-            .title(format!("JSON View: {}", title))
+            .title(format!(
+                "Query History ({})",
+                self.context.query_history.len()
+            ))
+            .title_bottom(
+                Line::from(" j/k: Nav | Enter: Load | Esc: Close ")
+                    .style(theme.muted())
+                    .alignment(Alignment::Right),
+            )
             .borders(Borders::ALL);
 
-        let syntax = SYNTAX_SET
-            .find_syntax_by_extension("json")
-            .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
-        // base16-ocean.dark is usually available in defaults, otherwise fall back to first available
-        let theme = THEME_SET
-            .themes
-            .get("base16-ocean.dark")
-            .unwrap_or_else(|| THEME_SET.themes.values().next().unwrap());
-        let mut h = HighlightLines::new(syntax, theme);
-
-        let lines: Vec<Line> = LinesWithEndings::from(json)
-            .map(|line| {
-                let ranges: Vec<(syntect::highlighting::Style, &str)> =
-                    h.highlight_line(line, &SYNTAX_SET).unwrap_or_default();
-                let spans: Vec<Span> = ranges
-                    .into_iter()
-                    .filter_map(|(style, content)| {
-                        into_span((style, content)).ok().map(|mut span| {
-                            span.style.bg = None; // Remove background color to adapt to terminal
-                            span
-                        })
-                    })
-                    .collect();
-                Line::from(spans)
+        let items: Vec<ListItem> = self
+            .context
+            .query_history
+            .iter()
+            .rev()
+            .map(|query| ListItem::new(Line::from(query_spans(theme, query))))
+            .collect();
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(theme.highlight())
+            .highlight_symbol(theme.highlight_symbol());
+
+        f.render_stateful_widget(list, area, state);
+    }
+
+    fn draw_saved_queries_popup(&self, f: &mut Frame, area: Rect, state: &mut ListState) {
+        let area = centered_rect(70, 60, area);
+        f.render_widget(Clear, area);
+        let theme = &self.context.theme;
+        let saved = &self.context.config.config.saved_queries;
+        let block = Block::default()
+            .title(format!("Saved Queries ({})", saved.len()))
+            .title_bottom(
+                Line::from(" j/k: Nav | Enter: Load | d: Delete | Esc: Close ")
+                    .style(theme.muted())
+                    .alignment(Alignment::Right),
+            )
+            .borders(Borders::ALL);
+
+        let items: Vec<ListItem> = saved
+            .iter()
+            .map(|query| {
+                let mut spans = vec![Span::styled(format!("{}  ", query.name), theme.accent())];
+                spans.extend(query_spans(theme, &QueryState::from(query)));
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(theme.highlight())
+            .highlight_symbol(theme.highlight_symbol());
+
+        f.render_stateful_widget(list, area, state);
+    }
+
+    fn draw_indexes_popup(
+        &self,
+        f: &mut Frame,
+        area: Rect,
+        state: &mut TableState,
+        indexes: &[mongo_core::bson::Document],
+    ) {
+        let area = centered_rect(70, 50, area);
+        f.render_widget(Clear, area);
+        let title = match self.context.selected_namespace() {
+            Some((db, coll)) => format!("Indexes: {}.{} ({})", db, coll, indexes.len()),
+            None => format!("Indexes ({})", indexes.len()),
+        };
+        let block = Block::default()
+            .title(title)
+            .title_bottom(
+                Line::from(" j/k: Nav | Esc: Close ")
+                    .style(self.context.theme.muted())
+                    .alignment(Alignment::Right),
+            )
+            .borders(Borders::ALL);
+
+        let flag = |spec: &mongo_core::bson::Document, name: &str| {
+            if spec.get_bool(name).unwrap_or(false) {
+                "yes"
+            } else {
+                ""
+            }
+        };
+        let rows = indexes.iter().map(|spec| {
+            let name = spec.get_str("name").unwrap_or("-").to_string();
+            // Keys are listed in spec order, which is the order MongoDB uses
+            let keys = spec
+                .get_document("key")
+                .map(|key| {
+                    key.iter()
+                        .map(|(field, dir)| format!("{}: {}", field, format_bson_cell(dir)))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_default();
+            Row::new(vec![
+                name,
+                keys,
+                flag(spec, "unique").to_string(),
+                flag(spec, "sparse").to_string(),
+            ])
+        });
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(30),
+                Constraint::Percentage(50),
+                Constraint::Percentage(10),
+                Constraint::Percentage(10),
+            ],
+        )
+        .header(
+            Row::new(vec!["Name", "Keys", "Unique", "Sparse"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(block)
+        .row_highlight_style(self.context.theme.highlight())
+        .highlight_symbol(self.context.theme.highlight_symbol());
+
+        f.render_stateful_widget(table, area, state);
+    }
+
+    fn draw_collection_stats_popup(
+        &self,
+        f: &mut Frame,
+        area: Rect,
+        namespace: &str,
+        stats: &mongo_core::bson::Document,
+    ) {
+        let area = centered_rect(50, 40, area);
+        f.render_widget(Clear, area);
+        let block = Block::default()
+            .title(format!("Stats: {}", namespace))
+            .title_bottom(
+                Line::from(" Esc: Close ")
+                    .style(self.context.theme.muted())
+                    .alignment(Alignment::Right),
+            )
+            .borders(Borders::ALL);
+
+        // Views report nothing and time-series buckets omit some fields
+        let number = |key: &str| match stats.get(key) {
+            Some(Bson::Int32(n)) => Some(*n as f64),
+            Some(Bson::Int64(n)) => Some(*n as f64),
+            Some(Bson::Double(n)) => Some(*n),
+            _ => None,
+        };
+        let count = |key: &str| number(key).map(|n| format!("{}", n as i64));
+        let bytes =
+            |key: &str| number(key).map(|n| format!("{} ({} bytes)", format_bytes(n), n as i64));
+        let fields = [
+            ("Documents", count("count")),
+            ("Size", bytes("size")),
+            ("Storage size", bytes("storageSize")),
+            ("Avg. document size", bytes("avgObjSize")),
+            ("Indexes", count("nindexes")),
+        ];
+
+        let lines: Vec<Line> = fields
+            .into_iter()
+            .map(|(label, value)| {
+                let value = match value {
+                    Some(value) => Span::raw(value),
+                    None => Span::styled("n/a", self.context.theme.muted()),
+                };
+                Line::from(vec![
+                    Span::styled(format!("{:<20}", label), self.context.theme.label()),
+                    value,
+                ])
             })
             .collect();
 
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    fn draw_explain_popup(
+        &self,
+        f: &mut Frame,
+        area: Rect,
+        explain: &mongo_core::bson::Document,
+        raw: bool,
+        offset: usize,
+    ) {
+        let area = centered_rect(70, 60, area);
+        f.render_widget(Clear, area);
+        let theme = &self.context.theme;
+        let hint = if raw {
+            " j/k: Scroll | r: Summary | Esc: Close "
+        } else {
+            " r: Show Raw | Esc: Close "
+        };
+        let block = Block::default()
+            .title("Explain")
+            .title_bottom(
+                Line::from(hint)
+                    .style(theme.muted())
+                    .alignment(Alignment::Right),
+            )
+            .borders(Borders::ALL);
+
+        if raw {
+            let json = serde_json::to_string_pretty(
+                &Bson::Document(explain.clone()).into_relaxed_extjson(),
+            )
+            .unwrap_or_default();
+            let lines: Vec<Line> = json.lines().map(|l| Line::raw(l.to_string())).collect();
+            let paragraph = Paragraph::new(lines)
+                .block(block)
+                .scroll((offset as u16, 0));
+            f.render_widget(paragraph, area);
+            return;
+        }
+
+        let stages = winning_plan_stages(explain);
+        let verdict = if stages.iter().any(|s| s.starts_with("COLLSCAN")) {
+            Span::styled("Collection scan: no index used", theme.error())
+        } else if stages.iter().any(|s| s.starts_with("IXSCAN")) {
+            Span::styled("Uses an index", theme.success())
+        } else {
+            Span::styled("No scan stage found", theme.muted())
+        };
+        let stats = explain.get_document("executionStats").ok();
+        let stat = |key: &str| {
+            stats
+                .and_then(|s| s.get(key))
+                .map(format_bson_cell)
+                .map_or_else(|| Span::styled("n/a", theme.muted()), Span::raw)
+        };
+        let row = |label: &str, value: Span<'static>| {
+            Line::from(vec![
+                Span::styled(format!("{:<20}", label), theme.label()),
+                value,
+            ])
+        };
+        let plan = if stages.is_empty() {
+            Span::styled("n/a", theme.muted())
+        } else {
+            Span::raw(stages.join(" <- "))
+        };
+        let lines = vec![
+            Line::from(verdict),
+            Line::default(),
+            row("Winning plan", plan),
+            row("Returned", stat("nReturned")),
+            row("Docs examined", stat("totalDocsExamined")),
+            row("Keys examined", stat("totalKeysExamined")),
+            row("Time (ms)", stat("executionTimeMillis")),
+        ];
+        f.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .wrap(Wrap { trim: false }),
+            area,
+        );
+    }
+
+    fn draw_aggregation_builder_popup(&self, f: &mut Frame, area: Rect) {
+        let area = centered_rect(80, 80, area);
+        f.render_widget(Clear, area);
+        let title = match self.context.selected_namespace() {
+            Some((db, coll)) => format!("Aggregation: {}.{}", db, coll),
+            None => "Aggregation".to_string(),
+        };
+        let block = Block::default().title(title).borders(Borders::ALL);
+        f.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([
+                Constraint::Min(3),    // Pipeline
+                Constraint::Length(1), // Error
+                Constraint::Length(1), // Help
+            ])
+            .split(area);
+
+        let mut widget = self.context.pipeline_input.clone();
+        widget.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Pipeline (JSON array of stages)")
+                .border_style(self.context.theme.border(true)),
+        );
+        widget.set_cursor_style(Style::default().add_modifier(Modifier::REVERSED));
+        f.render_widget(&widget, chunks[0]);
+
+        if let Some(error) = self
+            .context
+            .input_validation_errors
+            .get(&QueryField::Pipeline)
+        {
+            let error = Paragraph::new(error.as_str()).style(self.context.theme.error());
+            f.render_widget(error, chunks[1]);
+        }
+
+        let help = Paragraph::new("Enter: Run | Esc: Cancel").alignment(Alignment::Center);
+        f.render_widget(help, chunks[2]);
+    }
+
+    /// Lines of `json`, highlighted unless colors are off or the document
+    /// is too large; the flag tells the latter case apart.
+    fn json_lines<'a>(&self, json: &'a str) -> (Vec<Line<'a>>, bool) {
+        // Highlighting multi-megabyte documents stalls the UI
+        if self.context.theme.no_color {
+            (json.lines().map(Line::raw).collect(), false)
+        } else if json.len() > self.context.config.config.highlight_max_bytes {
+            (json.lines().map(Line::raw).collect(), true)
+        } else {
+            (highlight_json(json, &THEME_SET, JSON_THEME), false)
+        }
+    }
+
+    /// The selected document beside the table, following the selection.
+    fn draw_detail_drawer(&self, f: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .title(" Detail ")
+            .title_bottom(
+                Line::from("Space: Close")
+                    .style(self.context.theme.muted())
+                    .alignment(Alignment::Center),
+            )
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(self.context.theme.border(false));
+        let json = self
+            .context
+            .selected_document
+            .and_then(|idx| self.context.documents.get(idx))
+            .and_then(|doc| serde_json::to_string_pretty(doc).ok());
+        let Some(json) = json else {
+            let empty = Paragraph::new("No document selected")
+                .style(self.context.theme.muted())
+                .block(block);
+            f.render_widget(empty, area);
+            return;
+        };
+        let (lines, _) = self.json_lines(&json);
+        let paragraph = Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false });
+        f.render_widget(paragraph, area);
+    }
+
+    fn draw_json_popup(
+        &self,
+        f: &mut Frame,
+        area: Rect,
+        json: &str,
+        title: &str,
+        offset: &mut usize,
+        selection: Option<(usize, usize)>,
+    ) {
+        let area = centered_rect(80, 80, area);
+        f.render_widget(Clear, area);
+        let hint = if selection.is_some() {
+            " VISUAL — j/k: Extend | y: Copy | Esc: Cancel "
+        } else {
+            " j/k: Scroll | V: Select lines | Esc: Close "
+        };
+        let mut block = Block::default()
+            .title(format!("JSON View: {}", title))
+            .title_bottom(
+                Line::from(hint)
+                    .style(self.context.theme.muted())
+                    .alignment(Alignment::Right),
+            )
+            .borders(Borders::ALL);
+
+        let (mut lines, large) = self.json_lines(json);
+        if large {
+            block = block.title_bottom(
+                Line::from(" large document — highlighting disabled ")
+                    .style(self.context.theme.muted())
+                    .alignment(Alignment::Center),
+            );
+        }
+
+        if let Some((anchor, cursor)) = selection {
+            // Keep the cursor line inside the viewport while extending downwards
+            let height = area.height.saturating_sub(2).max(1) as usize;
+            if cursor >= *offset + height {
+                *offset = cursor + 1 - height;
+            }
+            let selected = self.context.theme.selection();
+            for line in lines
+                .iter_mut()
+                .take(anchor.max(cursor) + 1)
+                .skip(anchor.min(cursor))
+            {
+                line.style = selected;
+                for span in line.spans.iter_mut() {
+                    span.style = span.style.patch(selected);
+                }
+            }
+        }
+
         let paragraph = Paragraph::new(lines)
             .block(block)
             .wrap(Wrap { trim: false })
-            .scroll((offset as u16, 0));
+            .scroll((*offset as u16, 0));
         f.render_widget(paragraph, area);
     }
 
-    fn draw_help_popup(&self, f: &mut Frame, area: Rect, state: &mut TableState) {
-        let area = centered_rect(70, 70, area);
+    fn draw_json_tree_popup(&self, f: &mut Frame, area: Rect, tree: &mut JsonTree) {
+        let area = centered_rect(80, 80, area);
         f.render_widget(Clear, area);
+        let theme = &self.context.theme;
         let block = Block::default()
-            .title("Help (Scroll: j/k)")
+            .title(format!("Document: {}", tree.title))
+            .title_bottom(
+                Line::from(
+                    " j/k: Move | Enter: Fold | h/l: Collapse/Expand | y: Copy | Esc: Close ",
+                )
+                .style(theme.muted())
+                .alignment(Alignment::Right),
+            )
             .borders(Borders::ALL);
 
-        // Global
+        // Keep the cursor inside the viewport
+        let height = area.height.saturating_sub(2).max(1) as usize;
+        if tree.cursor < tree.offset {
+            tree.offset = tree.cursor;
+        } else if tree.cursor >= tree.offset + height {
+            tree.offset = tree.cursor + 1 - height;
+        }
+
+        let tree_lines = tree.lines();
+        let lines: Vec<Line> = tree_lines
+            .iter()
+            .enumerate()
+            .skip(tree.offset)
+            .take(height)
+            .map(|(idx, node)| {
+                let foldable = match node.value {
+                    Bson::Document(d) => !d.is_empty(),
+                    Bson::Array(a) => !a.is_empty(),
+                    _ => false,
+                };
+                let marker = match (foldable, node.expanded) {
+                    (false, _) => "  ",
+                    (true, true) => "▾ ",
+                    (true, false) => "▸ ",
+                };
+                let value = match node.value {
+                    Bson::Document(d) if node.expanded => {
+                        Span::styled(format!("{{{} fields}}", d.len()), theme.muted())
+                    }
+                    Bson::Document(d) if !d.is_empty() => {
+                        Span::styled(format!("{{... {} fields}}", d.len()), theme.muted())
+                    }
+                    Bson::Array(a) if node.expanded => {
+                        Span::styled(format!("[{} items]", a.len()), theme.muted())
+                    }
+                    Bson::Array(a) if !a.is_empty() => {
+                        Span::styled(format!("[... {} items]", a.len()), theme.muted())
+                    }
+                    Bson::Document(_) => Span::styled("{}", theme.muted()),
+                    Bson::Array(_) => Span::styled("[]", theme.muted()),
+                    // JSON escaping keeps multi-line strings on one row
+                    Bson::String(s) => Span::styled(
+                        serde_json::to_string(s).unwrap_or_default(),
+                        theme.json_value(node.value),
+                    ),
+                    other => Span::styled(format_bson_cell(other), theme.json_value(other)),
+                };
+                let line = Line::from(vec![
+                    Span::raw("  ".repeat(node.depth)),
+                    Span::raw(marker),
+                    Span::styled(format!("{}: ", node.key), theme.label()),
+                    value,
+                ]);
+                if idx == tree.cursor {
+                    line.style(theme.highlight())
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    /// Every shortcut as `(context, key, action)`: globals first, then panes.
+    fn shortcut_reference(&self) -> Vec<(&'static str, &'static str, &'static str)> {
         let mut rows = vec![
-            Row::new(vec!["Global", "q", "Quit"]),
-            Row::new(vec!["Global", "?", "Help"]),
-            Row::new(vec!["Global", "Tab", "Cycle Pane"]),
-            Row::new(vec!["Global", "1-4", "Switch Pane"]),
+            ("Global", "q", "Quit"),
+            ("Global", "Ctrl-c", "Quit (also from popups)"),
+            ("Global", "?", "Help"),
+            ("Global", "Tab", "Cycle Pane"),
+            ("Global", "1-5", "Switch Pane"),
+            ("Global", "!", "Run Command"),
+            ("Global", "L", "Server Log"),
+            ("Global", "A", "App Log"),
+            ("Global", "Ctrl-r", "Reconnect"),
+            ("Global", "Ctrl-Tab/]", "Next Tab"),
+            ("Global", "Ctrl-Shift-Tab/[", "Previous Tab"),
+            ("Global", "Ctrl-w", "Close Tab"),
+            ("Global", "Ctrl-Left/Right", "Narrow/Widen Sidebar"),
+            ("Insert Document", "Ctrl-s", "Insert"),
+            ("Edit Document", "Ctrl-s", "Save"),
+            ("Connection Form", "Ctrl-t", "Test Connection"),
+            ("Aggregation", "Enter", "Run Pipeline"),
+            ("Distinct Values", "Enter", "Add to Filter"),
+            ("Indexes", "Esc/i", "Close"),
+            ("Collection Stats", "Esc/s", "Close"),
+            ("Explain", "r", "Toggle Raw"),
+            ("Query History", "Enter", "Load Query"),
+            ("Query Builder", "Ctrl-s", "Save Query As"),
+            ("Query Builder", "Ctrl-g", "Guided Filter/Sort"),
+            ("Filter Builder", "Left/Right", "Change Field/Operator"),
+            ("Filter Builder", "Ctrl-n", "Add Row"),
+            ("Filter Builder", "Ctrl-d", "Remove Row"),
+            ("Filter Builder", "Enter", "Use Filter"),
+            ("Saved Queries", "Enter", "Load Query"),
+            ("Saved Queries", "d", "Delete"),
+            ("Update Many", "Tab", "Switch Field"),
+            ("Update Many", "Ctrl-s", "Count and Confirm"),
+            ("Help", "e", "Edit Config"),
+            ("Help", "w", "Write Shortcuts File"),
+            ("Document View", "Enter", "Fold/Unfold"),
+            ("Document View", "h/l", "Collapse/Expand"),
+            ("Document View", "y", "Copy Value"),
+            ("Text View", "V", "Select Lines"),
+            ("Text View", "y", "Copy Selected Lines"),
         ];
-
-        // Panes
-        let pane_shortcuts = self.registry.get_all_shortcuts();
-        for (pane_name, shortcuts) in pane_shortcuts {
+        for (pane_name, shortcuts) in self.registry.get_all_shortcuts() {
             for (key, action) in shortcuts {
-                rows.push(Row::new(vec![pane_name, key, action]));
+                rows.push((pane_name, key, action));
             }
         }
+        rows
+    }
+
+    /// Writes the shortcut reference as a markdown table in the data dir.
+    fn export_shortcuts(&self) -> Result<std::path::PathBuf> {
+        let mut out =
+            String::from("# mongo-tui shortcuts\n\n| Context | Key | Action |\n|---|---|---|\n");
+        for (context, key, action) in self.shortcut_reference() {
+            // Escape pipes so keys like `|` do not break the table
+            let key = key.replace('|', "\\|");
+            out.push_str(&format!("| {} | `{}` | {} |\n", context, key, action));
+        }
+        let dir = config::get_data_dir();
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("shortcuts.md");
+        std::fs::write(&path, out)?;
+        Ok(path)
+    }
+
+    fn draw_help_popup(&self, f: &mut Frame, area: Rect, state: &mut TableState) {
+        let area = centered_rect(70, 70, area);
+        f.render_widget(Clear, area);
+        let block = Block::default()
+            .title("Help (Scroll: j/k | e: Edit Config | w: Write to File)")
+            .title_bottom(
+                Line::from(format!(" Config: {} ", config::config_file().display()))
+                    .style(self.context.theme.muted()),
+            )
+            .borders(Borders::ALL);
+
+        let rows = self
+            .shortcut_reference()
+            .into_iter()
+            .map(|(context, key, action)| Row::new(vec![context, key, action]));
 
         let table = Table::new(
             rows,
@@ -493,7 +3179,8 @@ impl MongoViewer {
                 .style(Style::default().add_modifier(Modifier::BOLD)),
         )
         .block(block)
-        .row_highlight_style(Style::default().bg(Color::Blue));
+        .row_highlight_style(self.context.theme.highlight())
+        .highlight_symbol(self.context.theme.highlight_symbol());
 
         f.render_stateful_widget(table, area, state);
     }
@@ -521,19 +3208,198 @@ impl MongoViewer {
                     format!("[ ] {}", field)
                 };
                 ListItem::new(text).style(if is_selected {
-                    Style::default().fg(Color::Cyan)
+                    self.context.theme.label()
                 } else {
                     Style::default()
                 })
             })
             .collect();
 
-        let list = List::new(items)
-            .block(block)
-            .highlight_style(Style::default().bg(Color::Blue));
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(self.context.theme.highlight())
+            .highlight_symbol(self.context.theme.highlight_symbol());
+
+        f.render_stateful_widget(list, area, state);
+    }
+}
+
+/// Parses an optional query input; blank or invalid input yields `None`.
+fn parse_optional_document(input: &str) -> Option<mongo_core::bson::Document> {
+    if input.trim().is_empty() {
+        None
+    } else {
+        util::parse_extended_json(input).ok()
+    }
+}
+
+/// What to send to the server for an edited document: the edited document
+/// itself as a replacement, or a `$set`/`$unset` update when the editor only
+/// held some of the fields. Empty when nothing changed.
+fn document_change(
+    original: &mongo_core::bson::Document,
+    edited: mongo_core::bson::Document,
+    partial: bool,
+) -> Result<mongo_core::bson::Document, String> {
+    if edited
+        .get("_id")
+        .is_some_and(|id| Some(id) != original.get("_id"))
+    {
+        return Err("_id cannot be changed".to_string());
+    }
+    if !partial {
+        let unchanged = edited.len() == original.len()
+            && edited.iter().all(|(k, v)| original.get(k) == Some(v));
+        return Ok(if unchanged {
+            mongo_core::bson::Document::new()
+        } else {
+            edited
+        });
+    }
+
+    let mut set = mongo_core::bson::Document::new();
+    for (key, value) in edited.iter().filter(|(k, _)| *k != "_id") {
+        if original.get(key) != Some(value) {
+            set.insert(key, value.clone());
+        }
+    }
+    let mut unset = mongo_core::bson::Document::new();
+    for key in original
+        .keys()
+        .filter(|k| *k != "_id" && !edited.contains_key(k))
+    {
+        unset.insert(key, "");
+    }
+
+    let mut update = mongo_core::bson::Document::new();
+    if !set.is_empty() {
+        update.insert("$set", set);
+    }
+    if !unset.is_empty() {
+        update.insert("$unset", unset);
+    }
+    Ok(update)
+}
+
+/// Parses a JSON array of aggregation stages. Each stage must be an object.
+fn parse_pipeline(input: &str) -> Result<Vec<mongo_core::bson::Document>, String> {
+    let stages: Vec<serde_json::Value> =
+        serde_json::from_str(input).map_err(|e| format!("JSON syntax error: {}", e))?;
+    stages
+        .into_iter()
+        .enumerate()
+        .map(|(i, stage)| {
+            if !stage.is_object() {
+                return Err(format!("Stage {} is not an object", i + 1));
+            }
+            util::document_from_value(stage).map_err(|e| format!("Stage {}: {}", i + 1, e))
+        })
+        .collect()
+}
+
+/// Stage names of the winning plan from the top down, with the index name
+/// for index scans (`FETCH`, `IXSCAN age_1`). Handles the classic layout,
+/// the slot-based engine's `queryPlan` wrapper and sharded replies (first
+/// shard only).
+fn winning_plan_stages(explain: &mongo_core::bson::Document) -> Vec<String> {
+    let Ok(mut plan) = explain
+        .get_document("queryPlanner")
+        .and_then(|planner| planner.get_document("winningPlan"))
+    else {
+        return vec![];
+    };
+    if let Ok(shards) = plan.get_array("shards") {
+        match shards
+            .first()
+            .and_then(Bson::as_document)
+            .and_then(|shard| shard.get_document("winningPlan").ok())
+        {
+            Some(shard_plan) => plan = shard_plan,
+            None => return vec![],
+        }
+    }
+    if let Ok(query_plan) = plan.get_document("queryPlan") {
+        plan = query_plan;
+    }
+
+    let mut stages = vec![];
+    let mut stage = Some(plan);
+    while let Some(current) = stage {
+        if let Ok(name) = current.get_str("stage") {
+            stages.push(match current.get_str("indexName") {
+                Ok(index) => format!("{} {}", name, index),
+                Err(_) => name.to_string(),
+            });
+        }
+        // Plans with several inputs (OR, sort merge) follow the first
+        stage = current.get_document("inputStage").ok().or_else(|| {
+            current
+                .get_array("inputStages")
+                .ok()
+                .and_then(|inputs| inputs.first())
+                .and_then(Bson::as_document)
+        });
+    }
+    stages
+}
+
+/// Syntax-highlights a JSON string with `theme_name` from `themes`. Falls
+/// back to plain text when the JSON syntax or the theme is missing instead
+/// of panicking.
+fn highlight_json<'a>(json: &'a str, themes: &ThemeSet, theme_name: &str) -> Vec<Line<'a>> {
+    let syntax = SYNTAX_SET
+        .find_syntax_by_extension("json")
+        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+    let Some(theme) = themes.themes.get(theme_name) else {
+        return json.lines().map(Line::from).collect();
+    };
+    let mut h = HighlightLines::new(syntax, theme);
 
-        f.render_stateful_widget(list, area, state);
+    LinesWithEndings::from(json)
+        .map(|line| match h.highlight_line(line, &SYNTAX_SET) {
+            Ok(ranges) => {
+                let spans: Vec<Span> = ranges
+                    .into_iter()
+                    .filter_map(|(style, content)| {
+                        into_span((style, content)).ok().map(|mut span| {
+                            span.style.bg = None; // Remove background color to adapt to terminal
+                            span
+                        })
+                    })
+                    .collect();
+                Line::from(spans)
+            }
+            Err(_) => Line::from(line.trim_end_matches(['\r', '\n'])),
+        })
+        .collect()
+}
+
+/// A query on one line: the filter, then whichever of sort, projection and
+/// limit are set.
+fn query_spans(theme: &theme::Theme, query: &QueryState) -> Vec<Span<'static>> {
+    let one_line = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let filter = if query.filter.trim().is_empty() {
+        "{}".to_string()
+    } else {
+        one_line(&query.filter)
+    };
+    let mut spans = vec![Span::raw(filter)];
+    for (label, text) in [
+        ("sort", &query.sort),
+        ("projection", &query.projection),
+        ("limit", &query.limit),
+    ] {
+        if !text.trim().is_empty() {
+            spans.push(Span::styled(format!("  {}: ", label), theme.label()));
+            spans.push(Span::raw(one_line(text)));
+        }
     }
+    spans
+}
+
+/// Trimmed text of a single-line input, `None` when blank.
+fn optional_text(input: &TextArea) -> Option<String> {
+    Some(input.lines().join("").trim().to_string()).filter(|text| !text.is_empty())
 }
 
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
@@ -558,138 +3424,850 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
 
 impl Component for MongoViewer {
     fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
-        self.context.action_tx = Some(tx);
+        self.context.action_tx = Some(tab_sender(self.tabs[self.active_tab].id, tx.clone()));
+        self.action_tx = Some(tx);
         Ok(())
     }
 
     fn register_config_handler(&mut self, config: Config) -> Result<()> {
-        self.context.connections = config.config.connections;
+        self.context.connections = config.config.connections.clone();
+        if let Some(conn) = crate::config::Connection::from_env() {
+            self.context.connections.insert(0, conn);
+        }
+        for (context, _) in self.tabs.iter_mut().filter_map(|t| t.parked.as_mut()) {
+            context.connections = self.context.connections.clone();
+            apply_config(context, config.clone());
+        }
+        apply_config(&mut self.context, config);
         Ok(())
     }
 
     fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
         // 1. Handle Popups first
         if !matches!(self.popup_state, PopupState::None) {
-            return self.handle_popup_events(key);
+            let result = self.handle_popup_events(key);
+            if matches!(self.popup_state, PopupState::None) {
+                // Closing the editor saves or discards its edits
+                self.has_unsaved_edits = false;
+            }
+            return result;
         }
 
-        // 2. Global Shortcuts
-        match key.code {
-            KeyCode::Char('q') => return Ok(Some(Action::Quit)),
-            KeyCode::Char('?') => {
-                let mut state = TableState::default();
-                state.select(Some(0));
-                self.popup_state = PopupState::Help(state);
-                return Ok(Some(Action::Render));
-            }
-            KeyCode::Char('c') if self.registry.active_pane_id() == Some(self.conn_pane_id) => {
-                return Ok(Some(Action::OpenConnectionManager));
-            }
-            KeyCode::Tab => {
-                self.registry.cycle_next();
-                return Ok(Some(Action::Render));
-            }
-            KeyCode::Char('1') => {
-                self.registry.set_active(self.conn_pane_id);
-                return Ok(Some(Action::Render));
-            }
-            KeyCode::Char('2') => {
-                self.registry.set_active(self.db_pane_id);
+        // 2. Esc backs out one level at a time: a running export first, then
+        //    the pane's inline state (running query, filter input, active
+        //    filter), then its selection
+        if key.code == KeyCode::Esc {
+            if let Some(cancel) = &self.export_cancel {
+                cancel.store(true, Ordering::Relaxed);
+                self.context.set_status("Cancelling export...");
                 return Ok(Some(Action::Render));
             }
-            KeyCode::Char('3') => {
-                self.registry.set_active(self.query_pane_id);
-                return Ok(Some(Action::Render));
+            if let Some(action) = self.registry.handle_key_event(key, &mut self.context)? {
+                return Ok(Some(action));
             }
-            KeyCode::Char('4') => {
-                self.registry.set_active(self.doc_pane_id);
-                return Ok(Some(Action::Render));
+            if let Some(pane) = self.registry.get_active_pane() {
+                pane.clear_selection(&mut self.context);
             }
-            _ => {}
+            return Ok(Some(Action::Render));
         }
 
-        // 3. Active Pane
-        let result = self.registry.handle_key_event(key, &mut self.context)?;
-        if let Some(action) = result {
-            // Handle internal actions immediately
-            match action {
-                Action::OpenConnectionManager => {
-                    let mut name = TextArea::default();
-                    name.set_placeholder_text("Connection Name");
-                    let mut uri = TextArea::default();
-                    uri.set_placeholder_text("mongodb://localhost:27017");
-                    self.popup_state = PopupState::ConnectionManager {
-                        name: Box::new(name),
-                        uri: Box::new(uri),
-                        is_editing_uri: false,
-                    };
+        // 3. Global Shortcuts (skipped while a pane is taking text input)
+        if self.registry.active_pane_captures_input() {
+            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                return Ok(Some(Action::Quit));
+            }
+            return self.registry.handle_key_event(key, &mut self.context);
+        }
+        // User keybindings take precedence over the defaults below
+        let bound = self.match_keybinding(key);
+        if bound.is_none() {
+            match key.code {
+                KeyCode::Char('q') => return Ok(Some(Action::Quit)),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(Some(Action::Quit))
+                }
+                KeyCode::Char('?') => {
+                    let mut state = TableState::default();
+                    state.select(Some(0));
+                    self.popup_state = PopupState::Help(state);
                     return Ok(Some(Action::Render));
                 }
-                Action::OpenQueryBuilder => {
-                    self.popup_state = PopupState::QueryBuilder {
-                        active_field: QueryField::Filter,
+                KeyCode::Char('!') => return Ok(Some(Action::OpenCommandRunner)),
+                KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    let Some(uri) = self.context.connected_uri.clone() else {
+                        self.context.set_status("No connection to retry yet");
+                        return Ok(Some(Action::Render));
                     };
+                    return Ok(Some(Action::Connect(uri)));
+                }
+                KeyCode::Char('L') => return Ok(Some(Action::LoadServerLog)),
+                KeyCode::Char('A') => return Ok(Some(Action::ShowLogs)),
+                KeyCode::Left if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.resize_sidebar(-1);
                     return Ok(Some(Action::Render));
                 }
-                Action::OpenJsonPopup(json, title) => {
-                    self.popup_state = PopupState::JsonViewer(json, title, 0);
+                KeyCode::Right if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.resize_sidebar(1);
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Char('c') if self.registry.active_pane_id() == Some(self.conn_pane_id) => {
+                    self.open_connection_manager(None);
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Tab if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(Some(Action::NextTab))
+                }
+                KeyCode::BackTab if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(Some(Action::PreviousTab))
+                }
+                KeyCode::Char(']') => return Ok(Some(Action::NextTab)),
+                KeyCode::Char('[') => return Ok(Some(Action::PreviousTab)),
+                KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(Some(Action::CloseTab))
+                }
+                KeyCode::Tab => {
+                    self.registry.cycle_next();
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Char('1') => {
+                    self.registry.set_active(self.conn_pane_id);
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Char('2') => {
+                    self.registry.set_active(self.db_pane_id);
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Char('3') => {
+                    self.registry.set_active(self.query_pane_id);
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Char('4') => {
+                    self.registry.set_active(self.doc_pane_id);
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Char('5') => {
+                    self.registry.set_active(self.schema_pane_id);
+                    return Ok(Some(Action::Render));
+                }
+                _ => {}
+            }
+        }
+
+        // 4. Active Pane
+        let result = match bound {
+            Some(action) => Some(action),
+            None => self.registry.handle_key_event(key, &mut self.context)?,
+        };
+        self.dispatch_pane_action(result)
+    }
+
+    fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
+        // Popups are keyboard-only
+        if !matches!(self.popup_state, PopupState::None) {
+            return Ok(None);
+        }
+        let Some(id) = self.registry.pane_at(mouse.column, mouse.row) else {
+            return Ok(None);
+        };
+        let result = match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.registry.set_active(id);
+                self.registry
+                    .handle_mouse_event(id, mouse, &mut self.context)?
+                    .or(Some(Action::Render))
+            }
+            MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => self
+                .registry
+                .handle_mouse_event(id, mouse, &mut self.context)?,
+            _ => None,
+        };
+        self.dispatch_pane_action(result)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match &action {
+            // Results of the active tab are handled as usual; the others wait
+            Action::ForTab(id, inner) => {
+                if *id == self.tabs[self.active_tab].id {
+                    return self.update(*inner.clone());
+                }
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == *id) {
+                    tab.pending.push(*inner.clone());
+                }
+                return Ok(None);
+            }
+            Action::NextTab => self.cycle_tab(1)?,
+            Action::PreviousTab => self.cycle_tab(-1)?,
+            Action::CloseTab => self.close_tab()?,
+            Action::Tick => {
+                self.context.tick_loading();
+                self.context.expire_status();
+                // Skip a beat rather than stack refreshes on a slow query
+                let interval =
+                    Duration::from_secs(self.context.config.config.auto_refresh_secs.max(1));
+                if let Some(since) = self.context.auto_refresh {
+                    if !self.context.is_loading(LoadingKind::Documents)
+                        && since.elapsed() >= interval
+                    {
+                        self.context.auto_refresh = Some(Instant::now());
+                        // Started right here so the query it begins is the
+                        // one marked as running in the background
+                        self.update(Action::RefreshDocuments)?;
+                        self.context.query_in_background = true;
+                    }
+                }
+                if self.context.connected_uri.is_some()
+                    && !self.heartbeat_in_flight
+                    && self.last_heartbeat.elapsed() >= HEARTBEAT_INTERVAL
+                {
+                    self.heartbeat_in_flight = true;
+                    let mongo_core = self.context.mongo_core.clone();
+                    let tx = self.context.action_tx.clone();
+                    tokio::spawn(async move {
+                        if let Some(tx) = tx {
+                            let alive = mongo_core.is_connected().await
+                                && mongo_core
+                                    .ping_current(CONNECTION_TEST_TIMEOUT)
+                                    .await
+                                    .is_ok();
+                            let _ = tx.send(Action::ConnectionStatus(alive));
+                        }
+                    });
+                }
+            }
+            Action::ConnectionStatus(alive) => {
+                self.context.finish_loading(LoadingKind::Connecting);
+                self.heartbeat_in_flight = false;
+                self.last_heartbeat = Instant::now();
+                if !alive && self.context.connection_alive == Some(true) {
+                    self.context
+                        .set_status("Lost connection to the server (Ctrl-r to reconnect)");
+                }
+                self.context.connection_alive = Some(*alive);
+            }
+            Action::SaveConnection(connection) => {
+                self.context.connections.push(connection.clone());
+                self.context.selected_connection = Some(self.context.connections.len() - 1);
+                self.persist_connections();
+            }
+            Action::TestConnection(uri, options) => {
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                let uri = uri.clone();
+                let options = options.clone();
+                // Runs off the UI thread; the result comes back as an action
+                tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        let result = mongo_core
+                            .ping(&uri, &options, CONNECTION_TEST_TIMEOUT)
+                            .await
+                            .map(|latency| latency.as_millis() as u64)
+                            .map_err(|e| e.to_string());
+                        let _ = tx.send(Action::ConnectionTested(result));
+                    }
+                });
+            }
+            Action::ConnectionTested(result) => {
+                // Dropped if the form was closed while the test ran
+                if let PopupState::ConnectionManager { test_result, .. } = &mut self.popup_state {
+                    *test_result = Some(result.clone().map(Some));
+                }
+            }
+            Action::UpdateConnection(idx, connection) if *idx < self.context.connections.len() => {
+                // Takes effect on the next Connect; the open client is left alone
+                self.context.connections[*idx] = connection.clone();
+                if !connection.transient {
+                    self.persist_connections();
+                }
+                self.context
+                    .set_status(format!("Updated connection '{}'", connection.name));
+            }
+            Action::DeleteConnection(idx) if *idx < self.context.connections.len() => {
+                // Only the saved entry goes away; an open client stays connected
+                let removed = self.context.connections.remove(*idx);
+                let len = self.context.connections.len();
+                self.context.selected_connection = match self.context.selected_connection {
+                    _ if len == 0 => None,
+                    Some(sel) if sel > *idx || sel == len => Some(sel - 1),
+                    other => other,
+                };
+                if !removed.transient {
+                    self.persist_connections();
+                }
+                self.context
+                    .set_status(format!("Removed connection '{}'", removed.name));
+            }
+            Action::Connect(uri) => {
+                // Another server opens in a new tab; the same one reconnects
+                // in place
+                let other_server = self
+                    .context
+                    .connected_uri
+                    .as_ref()
+                    .is_some_and(|current| current != uri);
+                if other_server {
+                    // The export streams from this tab's client; a new tab
+                    // would park it, and reconnecting would replace it
+                    if self.export_cancel.is_some() {
+                        self.context
+                            .set_status("Finish or cancel the export first (Esc)");
+                        return Ok(Some(Action::Render));
+                    }
+                    self.open_tab()?;
+                }
+                self.context.start_loading(LoadingKind::Connecting);
+                self.context.connected_uri = Some(uri.clone());
+                self.context.connection_alive = None;
+                self.context.default_database = mongo_core::MongoCore::uri_default_database(uri);
+                self.context.databases_loaded = false;
+                let options = self
+                    .context
+                    .connections
+                    .iter()
+                    .find(|c| c.uri == *uri)
+                    .map(|c| c.connect_options())
+                    .unwrap_or_default();
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                let uri = uri.clone();
+                tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        if let Err(e) = mongo_core.connect(&uri, &options).await {
+                            let _ = tx.send(Action::ConnectionStatus(false));
+                            let _ =
+                                tx.send(Action::TaskFailed(LoadingKind::Connecting, e.to_string()));
+                        } else {
+                            // The driver connects lazily; ping to know it really can
+                            let alive = mongo_core
+                                .ping_current(CONNECTION_TEST_TIMEOUT)
+                                .await
+                                .is_ok();
+                            let _ = tx.send(Action::ConnectionStatus(alive));
+                            let _ = tx.send(Action::RefreshDatabases);
+                        }
+                    }
+                });
+            }
+            Action::OpenNamespace(db_name, coll_name) => {
+                self.context.startup_namespace = Some((db_name.clone(), coll_name.clone()));
+            }
+            Action::RefreshDatabases => {
+                self.context.start_loading(LoadingKind::Databases);
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        match mongo_core.list_databases().await {
+                            Ok(dbs) => {
+                                let _ = tx.send(Action::DatabasesLoaded(dbs));
+                            }
+                            Err(e) => {
+                                let _ = tx.send(Action::TaskFailed(
+                                    LoadingKind::Databases,
+                                    e.to_string(),
+                                ));
+                            }
+                        }
+                    }
+                });
+            }
+            Action::LoadCollectionStats(db_name, coll_name) => {
+                self.context.start_loading(LoadingKind::Other);
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                let db_name = db_name.clone();
+                let coll_name = coll_name.clone();
+                tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        match mongo_core.collection_stats(&db_name, &coll_name).await {
+                            Ok(stats) => {
+                                let namespace = format!("{}.{}", db_name, coll_name);
+                                let _ = tx.send(Action::CollectionStatsLoaded(namespace, stats));
+                            }
+                            Err(e) => {
+                                let _ =
+                                    tx.send(Action::TaskFailed(LoadingKind::Other, e.to_string()));
+                            }
+                        }
+                    }
+                });
+            }
+            Action::SaveQuery(name) => {
+                let state = self.context.query_state();
+                let query = NamedQuery {
+                    name: name.clone(),
+                    filter: state.filter,
+                    sort: state.sort,
+                    projection: state.projection,
+                    limit: state.limit,
+                };
+                let saved = &mut self.context.config.config.saved_queries;
+                match saved.iter_mut().find(|q| q.name == *name) {
+                    Some(existing) => *existing = query,
+                    None => saved.push(query),
+                }
+                self.persist_saved_queries(format!("Saved query \"{}\"", name));
+            }
+            Action::DeleteSavedQuery(name) => {
+                self.context
+                    .config
+                    .config
+                    .saved_queries
+                    .retain(|q| q.name != *name);
+                self.persist_saved_queries(format!("Deleted query \"{}\"", name));
+            }
+            Action::ExplainQuery => {
+                let Some((db_name, coll_name)) = self
+                    .context
+                    .selected_namespace()
+                    .map(|(db, coll)| (db.to_string(), coll.to_string()))
+                else {
+                    self.context.set_status("Select a collection first");
+                    return Ok(Some(Action::Render));
+                };
+                let options = self.context.build_find_options();
+                self.context.start_loading(LoadingKind::Other);
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        let filter = options.filter.unwrap_or_default();
+                        match mongo_core
+                            .explain_find(&db_name, &coll_name, filter, options.sort)
+                            .await
+                        {
+                            Ok(explain) => {
+                                let _ = tx.send(Action::ExplainLoaded(explain));
+                            }
+                            Err(e) => {
+                                let _ =
+                                    tx.send(Action::TaskFailed(LoadingKind::Other, e.to_string()));
+                            }
+                        }
+                    }
+                });
+            }
+            Action::ExplainLoaded(explain) => {
+                self.context.finish_loading(LoadingKind::Other);
+                self.popup_state = PopupState::Explain {
+                    explain: explain.clone(),
+                    raw: false,
+                    offset: 0,
+                };
+            }
+            Action::CollectionStatsLoaded(namespace, stats) => {
+                self.context.finish_loading(LoadingKind::Other);
+                self.popup_state = PopupState::CollectionStats(namespace.clone(), stats.clone());
+            }
+            Action::LoadCollectionOptions(db_name, coll_name) => {
+                self.context.start_loading(LoadingKind::Other);
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                let db_name = db_name.clone();
+                let coll_name = coll_name.clone();
+                tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        match mongo_core.collection_options(&db_name, &coll_name).await {
+                            Ok(options) => {
+                                let json = serde_json::to_string_pretty(&options)
+                                    .unwrap_or_else(|e| e.to_string());
+                                let title = format!("{} / {} options", db_name, coll_name);
+                                let _ = tx.send(Action::OpenJsonPopup(json, title));
+                            }
+                            Err(e) => {
+                                let _ =
+                                    tx.send(Action::TaskFailed(LoadingKind::Other, e.to_string()));
+                            }
+                        }
+                    }
+                });
+            }
+            Action::EditConfig => {
+                let path = config::config_file().display().to_string();
+                // Without a clipboard the path is still worth showing
+                if !self.context.copy(path.clone(), &path) {
+                    self.context.set_status(path);
+                }
+            }
+            Action::OpenCommandRunner => {
+                let mut db = TextArea::default();
+                db.set_placeholder_text("admin");
+                db.insert_str(self.context.current_database());
+                let mut command = TextArea::default();
+                command.set_placeholder_text("{ \"serverStatus\": 1 }");
+                self.popup_state = PopupState::CommandRunner {
+                    db: Box::new(db),
+                    command: Box::new(command),
+                    is_editing_command: true,
+                    error: None,
+                    armed: None,
+                };
+            }
+            Action::RunCommand(db_name, command) => {
+                self.context.start_loading(LoadingKind::Other);
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                let db_name = db_name.clone();
+                let command = command.clone();
+                tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        let name = command.keys().next().cloned().unwrap_or_default();
+                        match mongo_core.run_command(&db_name, command).await {
+                            Ok(reply) => {
+                                let json = serde_json::to_string_pretty(&reply)
+                                    .unwrap_or_else(|e| e.to_string());
+                                let title = format!("{} / {}", db_name, name);
+                                let _ = tx.send(Action::OpenJsonPopup(json, title));
+                            }
+                            Err(e) => {
+                                let _ =
+                                    tx.send(Action::TaskFailed(LoadingKind::Other, e.to_string()));
+                            }
+                        }
+                    }
+                });
+            }
+            Action::InsertDocument(json) => {
+                let Some((db_name, coll_name)) = self
+                    .context
+                    .selected_namespace()
+                    .map(|(db, coll)| (db.to_string(), coll.to_string()))
+                else {
+                    return Ok(None);
+                };
+                let doc = match util::parse_extended_json(json) {
+                    Ok(doc) => doc,
+                    Err(e) => {
+                        self.popup_state = PopupState::Error(e.to_string());
+                        return Ok(Some(Action::Render));
+                    }
+                };
+                self.context.start_loading(LoadingKind::Documents);
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        match mongo_core.insert_document(&db_name, &coll_name, doc).await {
+                            Ok(id) => {
+                                let _ = tx.send(Action::DocumentInserted(id.to_string()));
+                                let _ = tx.send(Action::RefreshDocuments);
+                            }
+                            Err(e) => {
+                                let _ = tx.send(Action::TaskFailed(
+                                    LoadingKind::Documents,
+                                    e.to_string(),
+                                ));
+                            }
+                        }
+                    }
+                });
+            }
+            Action::SaveDocumentEdit(filter, change) => {
+                let Some((db_name, coll_name)) = self
+                    .context
+                    .selected_namespace()
+                    .map(|(db, coll)| (db.to_string(), coll.to_string()))
+                else {
+                    return Ok(None);
+                };
+                self.context.start_loading(LoadingKind::Documents);
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                let filter = filter.clone();
+                let change = change.clone();
+                tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        let is_update = change.keys().next().is_some_and(|k| k.starts_with('$'));
+                        let result = if is_update {
+                            mongo_core
+                                .update_document(&db_name, &coll_name, filter, change)
+                                .await
+                        } else {
+                            mongo_core
+                                .replace_document(&db_name, &coll_name, filter, change)
+                                .await
+                        };
+                        match result {
+                            Ok(modified) => {
+                                let _ = tx.send(Action::DocumentSaved(modified));
+                                let _ = tx.send(Action::RefreshDocuments);
+                            }
+                            Err(e) => {
+                                let _ = tx.send(Action::TaskFailed(
+                                    LoadingKind::Documents,
+                                    e.to_string(),
+                                ));
+                            }
+                        }
+                    }
+                });
+            }
+            Action::DocumentSaved(modified) => {
+                self.context.set_status(if *modified == 0 {
+                    "Saved (document unchanged on server)".to_string()
+                } else {
+                    "Document saved".to_string()
+                });
+            }
+            Action::DeleteDocument(filter) => {
+                let Some((db_name, coll_name)) = self
+                    .context
+                    .selected_namespace()
+                    .map(|(db, coll)| (db.to_string(), coll.to_string()))
+                else {
+                    return Ok(None);
+                };
+                self.context.start_loading(LoadingKind::Documents);
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                let filter = filter.clone();
+                tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        match mongo_core
+                            .delete_document(&db_name, &coll_name, filter)
+                            .await
+                        {
+                            Ok(deleted) => {
+                                let _ = tx.send(Action::DocumentDeleted(deleted));
+                                let _ = tx.send(Action::RefreshDocuments);
+                            }
+                            Err(e) => {
+                                let _ = tx.send(Action::TaskFailed(
+                                    LoadingKind::Documents,
+                                    e.to_string(),
+                                ));
+                            }
+                        }
+                    }
+                });
+            }
+            Action::CountBulkUpdate(db_name, coll_name, filter, update) => {
+                self.context.start_loading(LoadingKind::Other);
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                let (db_name, coll_name) = (db_name.clone(), coll_name.clone());
+                let (filter, update) = (filter.clone(), update.clone());
+                tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        match mongo_core
+                            .count_documents(&db_name, &coll_name, Some(filter.clone()))
+                            .await
+                        {
+                            Ok(count) => {
+                                let _ = tx.send(Action::BulkUpdateCounted(
+                                    db_name, coll_name, filter, update, count,
+                                ));
+                            }
+                            Err(e) => {
+                                let _ = tx.send(Action::BulkUpdateCountFailed(e.to_string()));
+                            }
+                        }
+                    }
+                });
+            }
+            Action::BulkUpdateCounted(db_name, coll_name, filter, update, count) => {
+                self.context.finish_loading(LoadingKind::Other);
+                // Cancelled while counting
+                let PopupState::BulkUpdate { error, .. } = &mut self.popup_state else {
                     return Ok(Some(Action::Render));
+                };
+                if *count == 0 {
+                    *error = Some("No documents match the filter".to_string());
+                } else if filter.is_empty() {
+                    self.popup_state = PopupState::ConfirmUpdateAll {
+                        db: db_name.clone(),
+                        coll: coll_name.clone(),
+                        update: update.clone(),
+                        count: *count,
+                        input: Box::default(),
+                        error: None,
+                    };
+                } else {
+                    self.popup_state = PopupState::Confirm {
+                        message: format!(
+                            "Apply {} to {} documents matching {}?",
+                            update,
+                            format_count(*count),
+                            filter
+                        ),
+                        on_confirm: Box::new(Action::UpdateMany(
+                            db_name.clone(),
+                            coll_name.clone(),
+                            filter.clone(),
+                            update.clone(),
+                        )),
+                    };
                 }
-                Action::OpenFieldSelector(all_fields, visible_fields) => {
-                    let mut state = ListState::default();
-                    state.select(Some(0));
-                    self.popup_state = PopupState::FieldSelector(
-                        state,
-                        all_fields.clone(),
-                        visible_fields.clone(),
-                    );
-                    return Ok(Some(Action::Render));
+            }
+            Action::BulkUpdateCountFailed(msg) => {
+                self.context.finish_loading(LoadingKind::Other);
+                let msg = mongo_core::redact_uris(msg);
+                match &mut self.popup_state {
+                    PopupState::BulkUpdate { error, .. } => *error = Some(msg),
+                    _ => self.popup_state = PopupState::Error(msg),
                 }
-                _ => return Ok(Some(action)),
             }
-        }
-
-        Ok(None)
-    }
-
-    fn update(&mut self, action: Action) -> Result<Option<Action>> {
-        match &action {
-            Action::Tick => {
-                if self.is_loading {
-                    self.loading_frame = self.loading_frame.wrapping_add(1);
+            Action::UpdateMany(db_name, coll_name, filter, update) => {
+                self.context.start_loading(LoadingKind::Documents);
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                let (db_name, coll_name) = (db_name.clone(), coll_name.clone());
+                let (filter, update) = (filter.clone(), update.clone());
+                tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        match mongo_core
+                            .update_many(&db_name, &coll_name, filter, update)
+                            .await
+                        {
+                            Ok(modified) => {
+                                let _ = tx.send(Action::DocumentsUpdated(modified));
+                                let _ = tx.send(Action::RefreshDocuments);
+                            }
+                            Err(e) => {
+                                let _ = tx.send(Action::TaskFailed(
+                                    LoadingKind::Documents,
+                                    e.to_string(),
+                                ));
+                            }
+                        }
+                    }
+                });
+            }
+            Action::DocumentsUpdated(modified) => {
+                self.context
+                    .set_status(format!("Updated {} documents", format_count(*modified)));
+            }
+            Action::DropCollection(db_name, coll_name) => {
+                self.context.start_loading(LoadingKind::Databases);
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                let db_name = db_name.clone();
+                let coll_name = coll_name.clone();
+                tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        match mongo_core.drop_collection(&db_name, &coll_name).await {
+                            Ok(()) => {
+                                let _ = tx.send(Action::Dropped(db_name, Some(coll_name)));
+                                let _ = tx.send(Action::RefreshDatabases);
+                            }
+                            Err(e) => {
+                                let _ = tx.send(Action::TaskFailed(
+                                    LoadingKind::Databases,
+                                    e.to_string(),
+                                ));
+                            }
+                        }
+                    }
+                });
+            }
+            Action::DropDatabase(db_name) => {
+                self.context.start_loading(LoadingKind::Databases);
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                let db_name = db_name.clone();
+                tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        match mongo_core.drop_database(&db_name).await {
+                            Ok(()) => {
+                                let _ = tx.send(Action::Dropped(db_name, None));
+                                let _ = tx.send(Action::RefreshDatabases);
+                            }
+                            Err(e) => {
+                                let _ = tx.send(Action::TaskFailed(
+                                    LoadingKind::Databases,
+                                    e.to_string(),
+                                ));
+                            }
+                        }
+                    }
+                });
+            }
+            Action::Dropped(db_name, coll_name) => {
+                self.context.finish_loading(LoadingKind::Databases);
+                // The documents on screen may belong to what was just dropped
+                let shown_dropped = self.context.selected_namespace().is_some_and(|(db, coll)| {
+                    db == db_name && coll_name.as_deref().is_none_or(|c| c == coll)
+                });
+                if shown_dropped {
+                    self.context.selected_db_index = None;
+                    self.context.selected_coll_index = None;
+                    self.context.documents.clear();
+                    self.context.pagination.total_count = None;
                 }
+                self.context.set_status(match coll_name {
+                    Some(coll) => format!("Dropped collection {}.{}", db_name, coll),
+                    None => format!("Dropped database {}", db_name),
+                });
             }
-            Action::SaveConnection(name, uri) => {
-                self.context.connections.push(crate::config::Connection {
-                    name: name.clone(),
-                    uri: uri.clone(),
+            Action::DocumentDeleted(deleted) => {
+                // The deleted document was the only one on its page
+                if *deleted > 0
+                    && self.context.documents.len() <= 1
+                    && self.context.pagination.current_page > 0
+                {
+                    self.context.pagination.current_page -= 1;
+                }
+                self.context.set_status(if *deleted == 0 {
+                    "Document was already gone"
+                } else {
+                    "Document deleted"
                 });
-                self.context.selected_connection = Some(self.context.connections.len() - 1);
             }
-            Action::Connect(uri) => {
-                self.is_loading = true;
+            Action::DocumentInserted(id) => {
+                self.context.set_status(format!("Inserted _id: {}", id));
+            }
+            Action::ShowLogs => match logging::tail(APP_LOG_LINES) {
+                Ok(lines) if lines.is_empty() => self.context.set_status("The app log is empty"),
+                Ok(lines) => {
+                    let title =
+                        format!("App Log: {} (newest first)", logging::log_file().display());
+                    self.popup_state =
+                        PopupState::JsonViewer(lines.join("\n"), title, 0, None, None);
+                }
+                Err(e) => {
+                    self.popup_state =
+                        PopupState::Error(format!("Failed to read the app log: {}", e))
+                }
+            },
+            Action::LoadServerLog => {
+                self.context.start_loading(LoadingKind::Other);
                 let mongo_core = self.context.mongo_core.clone();
                 let tx = self.context.action_tx.clone();
-                let uri = uri.clone();
                 tokio::spawn(async move {
                     if let Some(tx) = tx {
-                        if let Err(e) = mongo_core.connect(&uri).await {
-                            let _ = tx.send(Action::Error(e.to_string()));
-                        } else {
-                            let _ = tx.send(Action::RefreshDatabases);
+                        match mongo_core.server_log().await {
+                            Ok(lines) => {
+                                let text = lines.into_iter().rev().collect::<Vec<_>>().join("\n");
+                                let title = "Server Log (newest first)".to_string();
+                                let _ = tx.send(Action::OpenJsonPopup(text, title));
+                            }
+                            Err(e) => {
+                                let _ =
+                                    tx.send(Action::TaskFailed(LoadingKind::Other, e.to_string()));
+                            }
                         }
                     }
                 });
             }
-            Action::RefreshDatabases => {
-                self.is_loading = true;
+            Action::LoadCollectionMetadata(db_name) => {
+                // Sizes come with the first expansion only
+                let has_stats = self
+                    .context
+                    .databases
+                    .iter()
+                    .any(|db| &db.name == db_name && db.stats.is_some());
+                if let (false, Some(tx)) = (has_stats, &self.context.action_tx) {
+                    let _ = tx.send(Action::LoadDatabaseStats(db_name.clone()));
+                }
                 let mongo_core = self.context.mongo_core.clone();
                 let tx = self.context.action_tx.clone();
+                let db_name = db_name.clone();
                 tokio::spawn(async move {
                     if let Some(tx) = tx {
-                        match mongo_core.list_databases().await {
-                            Ok(dbs) => {
-                                let _ = tx.send(Action::DatabasesLoaded(dbs));
+                        match mongo_core.collection_metadata(&db_name).await {
+                            Ok(collections) => {
+                                let _ =
+                                    tx.send(Action::CollectionMetadataLoaded(db_name, collections));
                             }
                             Err(e) => {
                                 let _ = tx.send(Action::Error(e.to_string()));
@@ -698,137 +4276,527 @@ impl Component for MongoViewer {
                     }
                 });
             }
+            Action::LoadDatabaseStats(db_name) => {
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                let db_name = db_name.clone();
+                tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        // Without dbStats privileges the sizes just stay unknown
+                        let stats = mongo_core
+                            .database_stats(&db_name)
+                            .await
+                            .unwrap_or_default();
+                        let _ = tx.send(Action::DatabaseStatsLoaded(db_name, stats));
+                    }
+                });
+            }
+            Action::DatabaseStatsLoaded(db_name, stats) => {
+                if let Some(db) = self
+                    .context
+                    .databases
+                    .iter_mut()
+                    .find(|d| &d.name == db_name)
+                {
+                    db.stats = Some(stats.clone());
+                }
+            }
+            Action::CollectionMetadataLoaded(db_name, collections) => {
+                if let Some(db_idx) = self
+                    .context
+                    .databases
+                    .iter()
+                    .position(|d| &d.name == db_name)
+                {
+                    // Keep the selected collection pointing at the same name
+                    let selected_name = self
+                        .context
+                        .selected_namespace()
+                        .filter(|(db, _)| db == db_name)
+                        .map(|(_, coll)| coll.to_string());
+                    self.context.databases[db_idx].collections = collections.clone();
+                    if let Some(name) = selected_name {
+                        self.context.selected_coll_index =
+                            collections.iter().position(|c| c.name == name);
+                    }
+                }
+            }
+            Action::OpenJsonPopup(json, title) => {
+                self.context.finish_loading(LoadingKind::Other);
+                self.popup_state =
+                    PopupState::JsonViewer(json.clone(), title.clone(), 0, None, None);
+            }
+            Action::SchemaLoaded(fields) => {
+                self.context.schema = fields.clone();
+            }
+            Action::SchemaFailed(msg) => {
+                self.context.set_status(format!(
+                    "Schema not inferred: {}",
+                    mongo_core::redact_uris(msg)
+                ));
+            }
             Action::DatabasesLoaded(dbs) => {
-                self.is_loading = false;
+                self.context.finish_loading(LoadingKind::Databases);
+                // A refresh may reorder the list; keep the selection by name
+                let selected = self
+                    .context
+                    .selected_namespace()
+                    .map(|(db, coll)| (db.to_string(), coll.to_string()));
+                // Sizes already fetched survive a refresh
+                let mut dbs = dbs.clone();
+                for db in dbs.iter_mut() {
+                    db.stats = self
+                        .context
+                        .databases
+                        .iter()
+                        .find(|old| old.name == db.name)
+                        .and_then(|old| old.stats.clone());
+                }
                 self.context.databases = dbs.clone();
+                let indexes = selected.and_then(|(db_name, coll_name)| {
+                    let db_idx = dbs.iter().position(|d| d.name == db_name)?;
+                    let coll_idx = dbs[db_idx]
+                        .collections
+                        .iter()
+                        .position(|c| c.name == coll_name)?;
+                    Some((db_idx, coll_idx))
+                });
+                (
+                    self.context.selected_db_index,
+                    self.context.selected_coll_index,
+                ) = indexes.unzip();
+                self.context.databases_loaded = true;
                 self.registry.set_active(self.db_pane_id);
+                // The databases pane clears it after revealing the node
+                if let Some((db_name, coll_name)) = self.context.startup_namespace.clone() {
+                    self.open_startup_namespace(&db_name, coll_name.as_deref());
+                }
             }
             Action::RefreshDocuments => {
+                // Inputs loaded from history or the config skip the builder's
+                // checks, so an invalid one would otherwise be dropped silently
+                if !self.context.validate_query_inputs() {
+                    self.context.auto_refresh = None;
+                    if matches!(self.popup_state, PopupState::None) {
+                        self.popup_state = PopupState::QueryBuilder {
+                            active_field: self
+                                .context
+                                .first_invalid_field()
+                                .unwrap_or(QueryField::Filter),
+                            sort_picker: None,
+                            filter_builder: None,
+                            original_filter: self.context.query_input.lines().join("\n"),
+                        };
+                    }
+                    return Ok(Some(Action::Render));
+                }
                 if let (Some(db_idx), Some(coll_idx)) = (
                     self.context.selected_db_index,
                     self.context.selected_coll_index,
                 ) {
                     if let Some(db) = self.context.databases.get(db_idx) {
                         if let Some(coll) = db.collections.get(coll_idx) {
-                            self.is_loading = true;
                             let db_name = db.name.clone();
                             let coll_name = coll.name.clone();
+                            let query = self.context.begin_query();
                             let mongo_core = self.context.mongo_core.clone();
                             let tx = self.context.action_tx.clone();
 
-                            let filter_str = self.context.query_input.lines().join("\n");
-                            let sort_str = self.context.sort_input.lines().join("\n");
-                            let proj_str = self.context.projection_input.lines().join("\n");
-                            let limit_str = self.context.limit_input.lines().join("");
-                            let current_page = self.context.pagination.current_page;
+                            // One extra document past the page tells whether a next
+                            // page exists; a smaller limit only trims what is shown
+                            let page_size = self.context.pagination.page_size.max(1);
+                            let rows = self.context.rows_per_page();
+                            let mut options = self.context.build_find_options();
+                            options.limit = Some(page_size as i64 + 1);
 
-                            // ... parsing logic (simplified here) ...
-                            // Ideally move parsing to context helper or util
+                            let namespace = (db_name.clone(), coll_name.clone());
+                            if self.context.schema_namespace.as_ref() != Some(&namespace) {
+                                self.context.schema_namespace = Some(namespace);
+                                self.context.schema.clear();
+                                self.load_schema(db_name.clone(), coll_name.clone());
+                            }
 
-                            tokio::spawn(async move {
+                            let task = tokio::spawn(async move {
                                 if let Some(tx) = tx {
-                                    let limit = limit_str.parse::<i64>().unwrap_or(10);
-                                    let skip = (current_page as i64 * limit) as u64;
-
-                                    let filter = if !filter_str.trim().is_empty() {
-                                        serde_json::from_str::<serde_json::Value>(&filter_str)
-                                            .ok()
-                                            .and_then(|v| mongo_core::bson::to_document(&v).ok())
-                                    } else {
-                                        None
-                                    };
-                                    let sort = if !sort_str.trim().is_empty() {
-                                        serde_json::from_str::<serde_json::Value>(&sort_str)
-                                            .ok()
-                                            .and_then(|v| mongo_core::bson::to_document(&v).ok())
-                                    } else {
-                                        None
-                                    };
-                                    let proj = if !proj_str.trim().is_empty() {
-                                        serde_json::from_str::<serde_json::Value>(&proj_str)
-                                            .ok()
-                                            .and_then(|v| mongo_core::bson::to_document(&v).ok())
-                                    } else {
-                                        None
-                                    };
-
-                                    let filter_clone_for_count = filter.clone();
+                                    let filter_clone_for_count = options.filter.clone();
 
                                     match mongo_core
-                                        .find_documents(
+                                        .find_documents_streaming(
                                             &db_name,
                                             &coll_name,
-                                            mongo_core::FindOptions {
-                                                filter,
-                                                projection: proj,
-                                                sort,
-                                                limit: Some(limit),
-                                                skip: Some(skip),
-                                            },
+                                            options,
+                                            page_size + 1,
                                         )
                                         .await
                                     {
-                                        Ok(docs) => {
+                                        Ok(mut docs) => {
+                                            let has_next = docs.len() > page_size;
+                                            docs.truncate(rows);
                                             // Fetch count
                                             match mongo_core
-                                                .count_documents(
-                                                    &db_name,
-                                                    &coll_name,
-                                                    filter_clone_for_count,
-                                                )
+                                                .count(&db_name, &coll_name, filter_clone_for_count)
                                                 .await
                                             {
                                                 Ok(count) => {
-                                                    let _ = tx
-                                                        .send(Action::DocumentsLoaded(docs, count));
+                                                    let _ = tx.send(Action::DocumentsLoaded(
+                                                        docs, count, has_next, query,
+                                                    ));
                                                 }
                                                 Err(e) => {
-                                                    let _ = tx.send(Action::Error(e.to_string()));
+                                                    let _ = tx.send(Action::QueryFailed(
+                                                        e.to_string(),
+                                                        query,
+                                                    ));
                                                 }
                                             }
                                         }
                                         Err(e) => {
-                                            let _ = tx.send(Action::Error(e.to_string()));
+                                            let _ =
+                                                tx.send(Action::QueryFailed(e.to_string(), query));
                                         }
                                     }
                                 }
                             });
+                            self.context.query_task = Some(task.abort_handle());
+                        }
+                    }
+                }
+            }
+            Action::SampleDocuments(db_name, coll_name) => {
+                let query = self.context.begin_query();
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                let db_name = db_name.clone();
+                let coll_name = coll_name.clone();
+                let size = self.context.config.config.sample_size.max(1);
+                self.context
+                    .set_status(format!("Sampling {} documents", size));
+                let task = tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        match mongo_core
+                            .sample_documents(&db_name, &coll_name, size)
+                            .await
+                        {
+                            Ok(docs) => {
+                                let count = DocumentCount::exact(docs.len() as u64);
+                                let _ = tx.send(Action::DocumentsLoaded(docs, count, false, query));
+                            }
+                            Err(e) => {
+                                let _ = tx.send(Action::QueryFailed(e.to_string(), query));
+                            }
+                        }
+                    }
+                });
+                self.context.query_task = Some(task.abort_handle());
+            }
+            Action::LoadDistinct(field) => {
+                let Some((db_name, coll_name)) = self
+                    .context
+                    .selected_namespace()
+                    .map(|(db, coll)| (db.to_string(), coll.to_string()))
+                else {
+                    return Ok(None);
+                };
+                self.context.start_loading(LoadingKind::Other);
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                let field = field.clone();
+                let filter = self.context.build_find_options().filter;
+                tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        match mongo_core
+                            .distinct_values(&db_name, &coll_name, &field, filter)
+                            .await
+                        {
+                            Ok(values) => {
+                                let _ = tx.send(Action::DistinctLoaded(field, values));
+                            }
+                            Err(e) => {
+                                let _ =
+                                    tx.send(Action::TaskFailed(LoadingKind::Other, e.to_string()));
+                            }
                         }
                     }
+                });
+            }
+            Action::DistinctLoaded(field, values) => {
+                self.context.finish_loading(LoadingKind::Other);
+                let truncated = values.len() > DISTINCT_LIMIT;
+                let values: Vec<Bson> = values.iter().take(DISTINCT_LIMIT).cloned().collect();
+                if values.is_empty() {
+                    self.context.set_status(format!("No values for {}", field));
+                } else {
+                    let mut state = ListState::default();
+                    state.select(Some(0));
+                    self.popup_state = PopupState::DistinctValues {
+                        field: field.clone(),
+                        values,
+                        state,
+                        truncated,
+                    };
                 }
             }
-            Action::DocumentsLoaded(docs, count) => {
-                self.is_loading = false;
+            Action::LoadIndexes => {
+                let Some((db_name, coll_name)) = self
+                    .context
+                    .selected_namespace()
+                    .map(|(db, coll)| (db.to_string(), coll.to_string()))
+                else {
+                    return Ok(None);
+                };
+                self.context.start_loading(LoadingKind::Other);
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        match mongo_core.list_indexes(&db_name, &coll_name).await {
+                            Ok(indexes) => {
+                                let _ = tx.send(Action::IndexesLoaded(indexes));
+                            }
+                            Err(e) => {
+                                let _ =
+                                    tx.send(Action::TaskFailed(LoadingKind::Other, e.to_string()));
+                            }
+                        }
+                    }
+                });
+            }
+            Action::IndexesLoaded(indexes) => {
+                self.context.finish_loading(LoadingKind::Other);
+                let mut state = TableState::default();
+                state.select(if indexes.is_empty() { None } else { Some(0) });
+                self.popup_state = PopupState::Indexes(state, indexes.clone());
+            }
+            Action::RunAggregation(pipeline) => {
+                let Some((db_name, coll_name)) = self
+                    .context
+                    .selected_namespace()
+                    .map(|(db, coll)| (db.to_string(), coll.to_string()))
+                else {
+                    return Ok(None);
+                };
+                let query = self.context.begin_query();
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                let pipeline = pipeline.clone();
+                let task = tokio::spawn(async move {
+                    if let Some(tx) = tx {
+                        match mongo_core
+                            .run_aggregate(&db_name, &coll_name, pipeline)
+                            .await
+                        {
+                            Ok(docs) => {
+                                let count = DocumentCount::exact(docs.len() as u64);
+                                let _ = tx.send(Action::DocumentsLoaded(docs, count, false, query));
+                            }
+                            Err(e) => {
+                                let _ = tx.send(Action::QueryFailed(e.to_string(), query));
+                            }
+                        }
+                    }
+                });
+                self.context.query_task = Some(task.abort_handle());
+            }
+            // A cancelled or superseded query; the panes never see it either
+            Action::DocumentsLoaded(.., query) | Action::QueryFailed(_, query)
+                if *query != self.context.query_id =>
+            {
+                return Ok(None);
+            }
+            Action::QueryFailed(msg, _) => {
+                self.context.query_task = None;
+                self.context.finish_loading(LoadingKind::Documents);
+                self.popup_state = PopupState::Error(mongo_core::redact_uris(msg));
+            }
+            Action::DocumentsLoaded(docs, count, has_next, _) => {
+                self.context.query_task = None;
+                self.context.finish_loading(LoadingKind::Documents);
                 self.context.documents = docs.clone();
-                self.context.pagination.total_count = Some(*count);
-                self.registry.set_active(self.doc_pane_id);
+                self.context.pagination.total_count = Some(count.value);
+                self.context.pagination.count_estimated = count.estimated;
+                self.context.pagination.has_next_page = *has_next;
+                self.context.record_query();
+                if !self.context.query_in_background {
+                    self.registry.set_active(self.doc_pane_id);
+                }
             }
-            Action::NextPage => {
-                if let Some(total) = self.context.pagination.total_count {
-                    let limit = self
-                        .context
-                        .limit_input
-                        .lines()
-                        .join("")
-                        .parse::<usize>()
-                        .unwrap_or(10);
-                    let current = self.context.pagination.current_page;
-                    let max_pages = (total as usize).div_ceil(limit);
-                    if current + 1 < max_pages {
-                        self.context.pagination.current_page += 1;
-                        return Ok(Some(Action::RefreshDocuments));
+            Action::NextPage if self.context.pagination.has_next_page => {
+                self.context.pagination.current_page += 1;
+                return Ok(Some(Action::RefreshDocuments));
+            }
+            Action::PreviousPage if self.context.pagination.current_page > 0 => {
+                self.context.pagination.current_page -= 1;
+                return Ok(Some(Action::RefreshDocuments));
+            }
+            Action::ExportDocuments(path, format) => {
+                if self.export_cancel.is_some() {
+                    self.context.set_status("An export is already running");
+                    return Ok(Some(Action::Render));
+                }
+                let Some((db_name, coll_name)) = self
+                    .context
+                    .selected_namespace()
+                    .map(|(db, coll)| (db.to_string(), coll.to_string()))
+                else {
+                    return Ok(None);
+                };
+                // Every matching document, not just the loaded page; a limit
+                // typed in the query builder still caps the export
+                let mut options = self.context.build_find_options();
+                options.skip = None;
+                options.limit = self.context.query_limit().map(|limit| limit as i64);
+                let expected = self
+                    .context
+                    .pagination
+                    .total_count
+                    .map(|total| options.limit.map_or(total, |limit| total.min(limit as u64)));
+
+                let cancel = Arc::new(AtomicBool::new(false));
+                self.export_cancel = Some(cancel.clone());
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                let path = path.clone();
+                let format = format.clone();
+                tokio::spawn(async move {
+                    let Some(tx) = tx else {
+                        return;
+                    };
+                    let export = async {
+                        let mut writer = ExportWriter::create(&path, format)?;
+                        let mut cursor = mongo_core
+                            .find_cursor(&db_name, &coll_name, options)
+                            .await?;
+                        while let Some(doc) = cursor.try_next().await? {
+                            if cancel.load(Ordering::Relaxed) {
+                                // Dropping the writer removes the partial file
+                                return anyhow::Ok(None);
+                            }
+                            writer.write(&doc)?;
+                            if writer.written().is_multiple_of(EXPORT_PROGRESS_EVERY) {
+                                let _ = tx.send(Action::ExportProgress(writer.written(), expected));
+                            }
+                        }
+                        Ok(Some(writer.finish()?))
+                    };
+                    let _ = match export.await {
+                        Ok(Some(written)) => tx.send(Action::ExportFinished(path, written)),
+                        Ok(None) => tx.send(Action::ExportCancelled),
+                        Err(e) => tx.send(Action::ExportFailed(format!(
+                            "Export to {} failed: {}",
+                            path.display(),
+                            e
+                        ))),
+                    };
+                });
+            }
+            Action::StartWatch => {
+                let Some(namespace) = self
+                    .context
+                    .selected_namespace()
+                    .map(|(db, coll)| (db.to_string(), coll.to_string()))
+                else {
+                    return Ok(None);
+                };
+                if let Some(task) = self.watch_task.take() {
+                    task.abort();
+                }
+                self.context.watching = Some(namespace.clone());
+                self.context
+                    .set_status(format!("Watching {}.{}", namespace.0, namespace.1));
+                let mongo_core = self.context.mongo_core.clone();
+                let tx = self.context.action_tx.clone();
+                self.watch_task = Some(tokio::spawn(async move {
+                    let Some(tx) = tx else {
+                        return;
+                    };
+                    match mongo_core
+                        .watch_collection(&namespace.0, &namespace.1)
+                        .await
+                    {
+                        Ok(stream) => {
+                            let mut stream = std::pin::pin!(stream);
+                            while let Some(event) = stream.next().await {
+                                match event {
+                                    Ok(event) => {
+                                        let _ = tx.send(Action::ChangeEvent(event));
+                                    }
+                                    Err(e) => {
+                                        let _ = tx.send(Action::Error(format!(
+                                            "Change stream closed: {}",
+                                            e
+                                        )));
+                                        break;
+                                    }
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            let _ = tx.send(Action::Error(format!("Cannot watch: {}", e)));
+                        }
                     }
+                    let _ = tx.send(Action::WatchStopped);
+                }));
+            }
+            Action::StopWatch => {
+                if let Some(task) = self.watch_task.take() {
+                    task.abort();
                 }
+                self.context.watching = None;
+                self.context.set_status("Stopped watching");
             }
-            Action::PreviousPage => {
-                if self.context.pagination.current_page > 0 {
-                    self.context.pagination.current_page -= 1;
+            Action::WatchStopped => {
+                self.watch_task = None;
+                self.context.watching = None;
+            }
+            Action::ExportProgress(written, expected) => {
+                let progress = match expected {
+                    Some(total) => format!("{} / {}", format_count(*written), format_count(*total)),
+                    None => format_count(*written),
+                };
+                self.context
+                    .set_status(format!("Exported {} (Esc to cancel)", progress));
+            }
+            Action::ExportFinished(path, written) => {
+                self.export_cancel = None;
+                self.context.set_status(format!(
+                    "Exported {} documents to {}",
+                    format_count(*written),
+                    path.display()
+                ));
+            }
+            Action::ExportCancelled => {
+                self.export_cancel = None;
+                self.context.set_status("Export cancelled");
+            }
+            Action::ExportFailed(msg) => {
+                self.export_cancel = None;
+                self.popup_state = PopupState::Error(mongo_core::redact_uris(msg));
+            }
+            Action::GoToPage(page) => {
+                let page = match self.context.page_count() {
+                    Some(pages) => (*page).min(pages - 1),
+                    // Without the count only pages up to the current one are known to exist
+                    None if *page > self.context.pagination.current_page => {
+                        self.context.set_status("Page count is still loading");
+                        return Ok(Some(Action::Render));
+                    }
+                    None => *page,
+                };
+                if page != self.context.pagination.current_page {
+                    self.context.pagination.current_page = page;
                     return Ok(Some(Action::RefreshDocuments));
                 }
             }
+            Action::TaskFailed(kind, msg) => {
+                self.context.finish_loading(*kind);
+                // Without a server or its databases, `--db` has nothing to open
+                if matches!(kind, LoadingKind::Connecting | LoadingKind::Databases) {
+                    self.context.startup_namespace = None;
+                }
+                self.popup_state = PopupState::Error(mongo_core::redact_uris(msg));
+            }
             Action::Error(msg) => {
-                self.is_loading = false;
-                self.popup_state = PopupState::Error(msg.clone());
+                self.popup_state = PopupState::Error(mongo_core::redact_uris(msg));
             }
             _ => {}
         }
@@ -852,80 +4820,133 @@ impl Component for MongoViewer {
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded);
 
-        if self.is_loading {
-            let spinner = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
-            let frame = self.loading_frame / 5 % spinner.len();
-            let text = format!(" Loading {} ", spinner[frame]);
+        if let Some(uri) = &self.context.connected_uri {
+            let host = mongo_core::MongoCore::uri_hosts(uri).unwrap_or_default();
+            let (dot, style, note) = match self.context.connection_alive {
+                Some(true) => ("●", self.context.theme.success(), ""),
+                Some(false) => ("●", self.context.theme.error(), " (disconnected)"),
+                None => ("○", self.context.theme.muted(), ""),
+            };
+            global_block = global_block.title(
+                Line::from(vec![
+                    Span::raw(" "),
+                    Span::styled(dot, style),
+                    Span::raw(format!(" {}{} ", host, note)),
+                ])
+                .alignment(Alignment::Left),
+            );
+        }
+
+        // Panes show their own spinners; the footer covers everything else
+        if let Some(spinner) = self.context.spinner(LoadingKind::Other) {
+            let text = format!(" Loading {} ", spinner);
             global_block = global_block.title_bottom(
                 Line::from(text)
-                    .style(Style::default().fg(Color::Cyan))
+                    .style(self.context.theme.label())
                     .alignment(Alignment::Left),
             );
         }
 
+        if let Some((msg, _)) = &self.context.status {
+            global_block = global_block.title_bottom(
+                Line::from(format!(" {} ", msg))
+                    .style(self.context.theme.success())
+                    .alignment(Alignment::Right),
+            );
+        }
+
         f.render_widget(global_block.clone(), area);
-        let inner_area = global_block.inner(area);
+        let mut inner_area = global_block.inner(area);
+
+        if self.tabs.len() > 1 {
+            let [tab_bar, rest] =
+                Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner_area);
+            self.draw_tab_bar(f, tab_bar);
+            inner_area = rest;
+        }
 
+        let (sidebar_width, query_rows) = self.layout_sizes(inner_area);
         let main_chunks = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(20), Constraint::Percentage(80)])
+            .constraints([Constraint::Length(sidebar_width), Constraint::Min(0)])
             .split(inner_area);
 
         let right_chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(6), Constraint::Min(0)])
+            .constraints([Constraint::Length(query_rows), Constraint::Min(0)])
             .split(main_chunks[1]);
 
+        let results_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(75), Constraint::Percentage(25)])
+            .split(right_chunks[1]);
+
+        // The drawer takes the right part of the documents area
+        let (documents_area, drawer_area) = if self.context.show_detail_drawer {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .split(results_chunks[0]);
+            (chunks[0], Some(chunks[1]))
+        } else {
+            (results_chunks[0], None)
+        };
+
         let sidebar_chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
             .split(main_chunks[0]);
 
-        let active_pane_id = self.registry.active_pane_id();
-
-        if let Some(pane) = self.registry.get_pane(self.conn_pane_id) {
-            let is_active = active_pane_id == Some(self.conn_pane_id);
-            pane.draw(f, sidebar_chunks[0], is_active, &self.context)?;
-        }
-        if let Some(pane) = self.registry.get_pane(self.db_pane_id) {
-            let is_active = active_pane_id == Some(self.db_pane_id);
-            pane.draw(f, sidebar_chunks[1], is_active, &self.context)?;
-        }
-
-        if let Some(pane) = self.registry.get_pane(self.query_pane_id) {
-            let is_active = active_pane_id == Some(self.query_pane_id);
-            pane.draw(f, right_chunks[0], is_active, &self.context)?;
-        }
-        if let Some(pane) = self.registry.get_pane(self.doc_pane_id) {
-            let is_active = active_pane_id == Some(self.doc_pane_id);
-            pane.draw(f, right_chunks[1], is_active, &self.context)?;
+        self.registry
+            .draw(self.conn_pane_id, f, sidebar_chunks[0], &self.context)?;
+        self.registry
+            .draw(self.db_pane_id, f, sidebar_chunks[1], &self.context)?;
+        self.registry
+            .draw(self.query_pane_id, f, right_chunks[0], &self.context)?;
+        self.registry
+            .draw(self.doc_pane_id, f, documents_area, &self.context)?;
+        if let Some(drawer_area) = drawer_area {
+            self.draw_detail_drawer(f, drawer_area);
         }
+        self.registry
+            .draw(self.schema_pane_id, f, results_chunks[1], &self.context)?;
 
         // Use swap to handle popup state mutable borrow
         let mut popup = std::mem::replace(&mut self.popup_state, PopupState::None);
 
-        match &mut popup {
-            PopupState::ConnectionManager {
-                name,
-                uri,
-                is_editing_uri,
-            } => self.draw_connection_manager_popup(f, area, name, uri, *is_editing_uri),
-            PopupState::QueryBuilder { active_field } => {
-                self.draw_query_builder_popup(f, area, active_field)
-            }
-            PopupState::JsonViewer(json, title, offset) => {
-                self.draw_json_popup(f, area, json, title, *offset)
-            }
-            PopupState::Help(state) => self.draw_help_popup(f, area, state),
-            PopupState::Error(msg) => self.draw_error_popup(f, area, msg),
-            PopupState::FieldSelector(state, all_fields, visible_fields) => {
-                self.draw_field_selector_popup(f, area, state, all_fields, visible_fields)
-            }
-            _ => {}
-        }
+        self.draw_popup(f, area, &mut popup);
 
         self.popup_state = popup;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlight_json_without_the_theme_falls_back_to_plain_lines() {
+        let json = "{\n  \"a\": 1\n}";
+        let lines = highlight_json(json, &THEME_SET, "no-such-theme");
+        assert_eq!(
+            lines,
+            vec![Line::from("{"), Line::from("  \"a\": 1"), Line::from("}")]
+        );
+
+        let lines = highlight_json(json, &ThemeSet::new(), JSON_THEME);
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn highlight_json_with_the_theme_keeps_the_text() {
+        let json = "{\n  \"a\": 1\n}";
+        let lines = highlight_json(json, &THEME_SET, JSON_THEME);
+        let text: Vec<String> = lines
+            .iter()
+            .map(|line| line.to_string().trim_end().to_string())
+            .collect();
+        assert_eq!(text, ["{", "  \"a\": 1", "}"]);
+    }
+}
@@ -1,11 +1,12 @@
 use color_eyre::eyre::Result;
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
 use ratatui::{
     prelude::*,
-    widgets::{Block, BorderType, Borders, List, ListItem, ListState},
+    widgets::{Block, BorderType, Borders, List, ListItem, ListState, Paragraph, Wrap},
 };
 
-use super::super::{context::MongoContext, pane_id::PaneId, registry::Pane};
+use super::super::{context::MongoContext, defs::LoadingKind, pane_id::PaneId, registry::Pane};
+use super::{clicked_line, ellipsize, scroll_key, split_detail};
 use crate::action::Action;
 
 pub struct ConnectionsPane {
@@ -35,8 +36,9 @@ impl Pane for ConnectionsPane {
         vec![
             ("c", "Add"),
             ("Enter", "Connect"),
+            ("e", "Edit"),
             ("j/k", "Nav"),
-            ("Del", "Remove"),
+            ("Del/x", "Remove"),
         ]
     }
 
@@ -68,6 +70,16 @@ impl Pane for ConnectionsPane {
                     }
                 }
             }
+            KeyCode::Char('e') => {
+                if let Some(idx) = ctx.selected_connection {
+                    return Ok(Some(Action::OpenConnectionEditor(idx)));
+                }
+            }
+            KeyCode::Delete | KeyCode::Char('x') => {
+                if let Some(idx) = ctx.selected_connection {
+                    return Ok(Some(Action::DeleteConnection(idx)));
+                }
+            }
             KeyCode::Enter => {
                 if let Some(idx) = ctx.selected_connection {
                     if let Some(conn) = ctx.connections.get(idx) {
@@ -80,6 +92,31 @@ impl Pane for ConnectionsPane {
         Ok(None)
     }
 
+    fn handle_mouse_event(
+        &mut self,
+        mouse: MouseEvent,
+        area: Rect,
+        ctx: &mut MongoContext,
+    ) -> Result<Option<Action>> {
+        if let Some(key) = scroll_key(&mouse) {
+            return self.handle_key_event(key, ctx);
+        }
+        let Some(line) = clicked_line(&mouse, area, 0) else {
+            return Ok(None);
+        };
+        let idx = self.list_state.offset() + line;
+        if idx >= ctx.connections.len() {
+            return Ok(None);
+        }
+        // Clicking the selected connection again connects, like Enter
+        if ctx.selected_connection == Some(idx) {
+            return self.handle_key_event(KeyEvent::from(KeyCode::Enter), ctx);
+        }
+        ctx.selected_connection = Some(idx);
+        self.list_state.select(Some(idx));
+        Ok(Some(Action::Render))
+    }
+
     fn draw(
         &mut self,
         f: &mut Frame,
@@ -88,34 +125,68 @@ impl Pane for ConnectionsPane {
         ctx: &MongoContext,
     ) -> Result<()> {
         // Show subset of shortcuts in title
-        let shortcuts_str = "c: Add | Enter: Connect | Del: Remove";
+        let shortcuts_str = "c: Add | e: Edit | Enter: Connect | Del: Remove";
+
+        let mut title = "[1] Connections".to_string();
+        if let Some(spinner) = ctx.spinner(LoadingKind::Connecting) {
+            title.push_str(&format!(" {}", spinner));
+        }
 
         let block = Block::default()
-            .title("[1] Connections")
-            .title_bottom(Line::from(shortcuts_str).alignment(Alignment::Center))
+            .title(ctx.theme.title(is_active, &title))
+            .title_bottom(
+                Line::from(shortcuts_str)
+                    .style(ctx.theme.hint(is_active))
+                    .alignment(Alignment::Center),
+            )
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(if is_active {
-                Style::default().fg(Color::Yellow)
-            } else {
-                Style::default()
-            });
+            .border_style(ctx.theme.border(is_active));
 
+        // Long names are shortened; the selected one is shown in full below
+        let max_name = block.inner(area).width as usize;
         let items: Vec<ListItem> = ctx
             .connections
             .iter()
-            .map(|conn| ListItem::new(conn.name.clone()))
+            .map(|conn| {
+                let name = ellipsize(&conn.name, max_name);
+                if conn.transient {
+                    ListItem::new(Line::from(vec![
+                        Span::raw(name),
+                        Span::styled(" (env)", ctx.theme.muted()),
+                    ]))
+                } else {
+                    ListItem::new(name)
+                }
+            })
             .collect();
 
-        // Sync state just in case
-        let mut state = self.list_state.clone();
-        state.select(ctx.selected_connection);
+        // Sync state just in case; the offset is kept for mouse clicks
+        self.list_state.select(ctx.selected_connection);
 
-        let list = List::new(items)
-            .block(block)
-            .highlight_style(Style::default().bg(Color::Blue));
+        let detail = ctx
+            .selected_connection
+            .and_then(|idx| ctx.connections.get(idx))
+            .map(|conn| conn.name.as_str())
+            .filter(|name| name.chars().count() > max_name);
 
-        f.render_stateful_widget(list, area, &mut state);
+        let list = List::new(items)
+            .highlight_style(ctx.theme.highlight())
+            .highlight_symbol(ctx.theme.highlight_symbol());
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        if let Some(detail) = detail {
+            let [list_area, detail_area] = split_detail(inner, detail);
+            f.render_stateful_widget(list, list_area, &mut self.list_state);
+            f.render_widget(
+                Paragraph::new(detail)
+                    .style(ctx.theme.muted())
+                    .wrap(Wrap { trim: false }),
+                detail_area,
+            );
+        } else {
+            f.render_stateful_widget(list, inner, &mut self.list_state);
+        }
         Ok(())
     }
 }
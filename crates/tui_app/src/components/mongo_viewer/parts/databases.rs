@@ -1,18 +1,36 @@
+use std::collections::HashSet;
+
 use color_eyre::eyre::Result;
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
     prelude::*,
-    widgets::{Block, BorderType, Borders},
+    widgets::{Block, BorderType, Borders, Paragraph, Wrap},
 };
 use tui_tree_widget::{Tree, TreeItem, TreeState};
 
-use super::super::{context::MongoContext, pane_id::PaneId, registry::Pane};
+use super::super::{
+    context::MongoContext,
+    defs::LoadingKind,
+    format::{format_bytes, format_count},
+    pane_id::PaneId,
+    registry::Pane,
+};
+use super::{ellipsize, scroll_key, split_detail};
 use crate::action::Action;
 
 pub struct DatabasesPane {
     id: PaneId,
     state: TreeState<String>,
     tree_items: Vec<TreeItem<'static, String>>,
+    // Set after `z`, waiting for the second key of a fold command
+    pending_fold: bool,
+    // Case-insensitive fuzzy (subsequence) filter over database/collection names
+    filter: String,
+    is_filtering: bool,
+    // Databases that were open before filtering, restored once it is cleared
+    opened_before_filter: Option<HashSet<Vec<String>>>,
+    // Inner width the labels were last shortened for; 0 before the first draw
+    label_width: usize,
 }
 
 impl DatabasesPane {
@@ -21,27 +39,196 @@ impl DatabasesPane {
             id,
             state: TreeState::default(),
             tree_items: vec![],
+            pending_fold: false,
+            filter: String::new(),
+            is_filtering: false,
+            opened_before_filter: None,
+            label_width: 0,
         }
     }
 
     fn rebuild_tree_items(&mut self, ctx: &MongoContext) {
+        let filter = self.filter.clone();
+        // Filtering opens databases to show matches; put the user's own
+        // expansion back once it ends
+        if filter.is_empty() {
+            if let Some(opened) = self.opened_before_filter.take() {
+                self.state.close_all();
+                for path in opened {
+                    self.state.open(path);
+                }
+            }
+        } else if self.opened_before_filter.is_none() {
+            self.opened_before_filter = Some(self.state.opened().clone());
+        }
+
         let mut items = vec![];
         for db in ctx.databases.iter() {
+            let db_matches = name_matches(&db.name, &filter);
             let mut children = vec![];
             for coll in db.collections.iter() {
+                if !db_matches && !name_matches(&coll.name, &filter) {
+                    continue;
+                }
                 // Use a composite ID: "db_name:coll_name" for uniqueness and stability
                 let id = format!("{}:{}", db.name, coll.name);
-                children.push(TreeItem::new_leaf(id, coll.name.clone()));
+                let name = self.shorten(&coll.name, COLLECTION_INDENT);
+                let mut label = highlight_match(&name, &filter, ctx.theme.accent());
+                if let Some(annotation) = coll.annotation() {
+                    label.push(Span::styled(
+                        format!(" ({})", annotation),
+                        ctx.theme.muted(),
+                    ));
+                }
+                if let Some(count) = coll.estimated_count {
+                    label.push(Span::styled(
+                        format!(" ~{}", format_count(count)),
+                        ctx.theme.muted(),
+                    ));
+                }
+                children.push(TreeItem::new_leaf(id, Line::from(label)));
+            }
+            if !db_matches && children.is_empty() {
+                continue;
+            }
+            // Show the collections that matched
+            if !filter.is_empty() && !children.is_empty() {
+                self.state.open(vec![db.name.clone()]);
             }
 
             // Use db.name for DB ID
             let id = db.name.clone();
+            let name = self.shorten(&db.name, DATABASE_INDENT);
+            let mut label = highlight_match(&name, &filter, ctx.theme.accent());
+            if db.collections.is_empty() {
+                label.push(Span::styled(" (empty)", ctx.theme.muted()));
+            } else if let Some(stats) = &db.stats {
+                let size = stats
+                    .total_size
+                    .map_or_else(|| "?".to_string(), |bytes| format_bytes(bytes as f64));
+                let collections = stats
+                    .collections
+                    .map_or_else(|| "?".to_string(), |n| n.to_string());
+                label.push(Span::styled(
+                    format!(" ({}, {} colls)", size, collections),
+                    ctx.theme.muted(),
+                ));
+            }
             items.push(
-                TreeItem::new(id, db.name.clone(), children).expect("Failed to create tree item"),
+                TreeItem::new(id, Line::from(label), children).expect("Failed to create tree item"),
             );
         }
         self.tree_items = items;
     }
+
+    /// Room left for a name at the given tree indent, or `None` before the
+    /// pane has been drawn.
+    fn name_budget(&self, indent: usize) -> Option<usize> {
+        (self.label_width > 0).then(|| self.label_width.saturating_sub(indent).max(1))
+    }
+
+    fn shorten(&self, name: &str, indent: usize) -> String {
+        match self.name_budget(indent) {
+            Some(max) => ellipsize(name, max),
+            None => name.to_string(),
+        }
+    }
+
+    /// Full `db` or `db.collection` of the selected node when its label had
+    /// to be shortened.
+    fn truncated_selection(&self) -> Option<String> {
+        let id = self.state.selected().last()?;
+        let (full, name, indent) = match id.split_once(':') {
+            Some((db_name, coll_name)) => (
+                format!("{}.{}", db_name, coll_name),
+                coll_name,
+                COLLECTION_INDENT,
+            ),
+            None => (id.clone(), id.as_str(), DATABASE_INDENT),
+        };
+        let max = self.name_budget(indent)?;
+        (name.chars().count() > max).then_some(full)
+    }
+
+    /// Collapses every database, moving the cursor up to its database if it
+    /// was on a collection so the selection stays visible.
+    fn collapse_all(&mut self) {
+        self.state.close_all();
+        if let Some(db_name) = self.state.selected().first().cloned() {
+            self.state.select(vec![db_name]);
+        }
+    }
+
+    fn expand_all(&mut self, ctx: &MongoContext) {
+        for db in ctx.databases.iter().filter(|db| !db.collections.is_empty()) {
+            self.state.open(vec![db.name.clone()]);
+        }
+    }
+
+    /// Forgets expansion and selection of databases that no longer exist,
+    /// keeping everything else (matched by name) across a refresh.
+    fn prune_missing(&mut self, ctx: &MongoContext) {
+        let exists = |name: &String| ctx.databases.iter().any(|db| &db.name == name);
+        let stale: Vec<Vec<String>> = self
+            .state
+            .opened()
+            .iter()
+            .filter(|path| !path.first().is_some_and(exists))
+            .cloned()
+            .collect();
+        for path in stale {
+            self.state.close(&path);
+        }
+        let selected_exists = match self.state.selected().last() {
+            Some(id) => match id.split_once(':') {
+                Some((db_name, coll_name)) => ctx.databases.iter().any(|db| {
+                    db.name == db_name && db.collections.iter().any(|c| c.name == coll_name)
+                }),
+                None => exists(id),
+            },
+            None => true,
+        };
+        if !selected_exists {
+            self.state.select(Vec::new());
+        }
+    }
+
+    /// Opens the URI's database if listed, else the first one with
+    /// collections, and moves the cursor onto it when nothing is selected.
+    fn auto_expand(&mut self, ctx: &MongoContext) {
+        let target = ctx
+            .databases
+            .iter()
+            .find(|db| ctx.default_database.as_deref() == Some(db.name.as_str()))
+            .or_else(|| ctx.databases.iter().find(|db| !db.collections.is_empty()));
+        if let Some(db) = target {
+            self.state.open(vec![db.name.clone()]);
+            if self.state.selected().is_empty() {
+                self.state.select(vec![db.name.clone()]);
+            }
+        }
+    }
+
+    /// Opens `db_name` and puts the cursor on it, or on `coll_name` inside it
+    /// when that collection exists.
+    fn reveal(&mut self, ctx: &MongoContext, db_name: String, coll_name: Option<String>) {
+        let Some(db) = ctx.databases.iter().find(|db| db.name == db_name) else {
+            return;
+        };
+        self.state.open(vec![db_name.clone()]);
+        let mut path = vec![db_name.clone()];
+        if let Some(coll) = coll_name.filter(|c| db.collections.iter().any(|x| x.name == *c)) {
+            path.push(format!("{}:{}", db_name, coll));
+        }
+        self.state.select(path);
+    }
+
+    /// The `(db, collection)` under the cursor, if a collection node is selected.
+    fn selected_collection(&self) -> Option<(String, String)> {
+        let last_id = self.state.selected().last()?;
+        let (db_name, coll_name) = last_id.split_once(':')?;
+        Some((db_name.to_string(), coll_name.to_string()))
+    }
 }
 
 impl Pane for DatabasesPane {
@@ -54,13 +241,45 @@ impl Pane for DatabasesPane {
     }
 
     fn get_shortcuts(&self) -> Vec<(&'static str, &'static str)> {
-        vec![("Enter", "Select/Expand"), ("j/k", "Nav")]
+        vec![
+            ("Enter", "Select/Expand"),
+            ("j/k", "Nav"),
+            ("o", "Options"),
+            ("s", "Stats"),
+            ("S", "Sample"),
+            ("D", "Drop"),
+            ("zM/zR", "Collapse/Expand All"),
+            ("/", "Filter"),
+            ("r", "Refresh"),
+        ]
+    }
+
+    fn clear_selection(&mut self, _ctx: &mut MongoContext) {
+        self.state.select(Vec::new());
+    }
+
+    fn captures_input(&self) -> bool {
+        self.is_filtering
     }
 
     fn update(&mut self, action: Action, ctx: &mut MongoContext) -> Result<Option<Action>> {
-        if let Action::DatabasesLoaded(_) = action {
-            self.rebuild_tree_items(ctx);
-            // Optionally expand the first one or restore state
+        match action {
+            Action::DatabasesLoaded(_) => {
+                self.prune_missing(ctx);
+                if let Some((db_name, coll_name)) = ctx.startup_namespace.take() {
+                    self.reveal(ctx, db_name, coll_name);
+                }
+                // Only a fresh tree is auto-expanded; refreshes keep what the user opened
+                let is_fresh = self.state.opened().is_empty() && self.state.selected().is_empty();
+                if ctx.config.config.auto_expand_first_db && is_fresh {
+                    self.auto_expand(ctx);
+                }
+                self.rebuild_tree_items(ctx);
+            }
+            Action::CollectionMetadataLoaded(_, _) | Action::DatabaseStatsLoaded(_, _) => {
+                self.rebuild_tree_items(ctx)
+            }
+            _ => {}
         }
         Ok(None)
     }
@@ -70,7 +289,54 @@ impl Pane for DatabasesPane {
         key: KeyEvent,
         ctx: &mut MongoContext,
     ) -> Result<Option<Action>> {
+        if self.is_filtering {
+            match key.code {
+                KeyCode::Esc => {
+                    self.is_filtering = false;
+                    self.filter.clear();
+                }
+                KeyCode::Enter => self.is_filtering = false,
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Char(c) => self.filter.push(c),
+                _ => return Ok(None),
+            }
+            self.rebuild_tree_items(ctx);
+            return Ok(Some(Action::Render));
+        }
+
+        if std::mem::take(&mut self.pending_fold) {
+            match key.code {
+                KeyCode::Char('M') => {
+                    self.collapse_all();
+                    self.rebuild_tree_items(ctx);
+                    return Ok(Some(Action::Render));
+                }
+                KeyCode::Char('R') => {
+                    self.expand_all(ctx);
+                    self.rebuild_tree_items(ctx);
+                    return Ok(Some(Action::Render));
+                }
+                _ => {}
+            }
+        }
+
         match key.code {
+            KeyCode::Char('z') => {
+                self.pending_fold = true;
+                return Ok(Some(Action::Render));
+            }
+            KeyCode::Char('r') => return Ok(Some(Action::RefreshDatabases)),
+            KeyCode::Char('/') => {
+                self.is_filtering = true;
+                return Ok(Some(Action::Render));
+            }
+            KeyCode::Esc if !self.filter.is_empty() => {
+                self.filter.clear();
+                self.rebuild_tree_items(ctx);
+                return Ok(Some(Action::Render));
+            }
             KeyCode::Char('j') | KeyCode::Down => {
                 self.state.key_down();
                 return Ok(Some(Action::Render));
@@ -79,6 +345,31 @@ impl Pane for DatabasesPane {
                 self.state.key_up();
                 return Ok(Some(Action::Render));
             }
+            KeyCode::Char('o') => {
+                if let Some((db_name, coll_name)) = self.selected_collection() {
+                    return Ok(Some(Action::LoadCollectionOptions(db_name, coll_name)));
+                }
+            }
+            KeyCode::Char('s') => {
+                if let Some((db_name, coll_name)) = self.selected_collection() {
+                    return Ok(Some(Action::LoadCollectionStats(db_name, coll_name)));
+                }
+            }
+            KeyCode::Char('D') => {
+                if let Some((db_name, coll_name)) = self.selected_collection() {
+                    return Ok(Some(Action::RequestDrop(db_name, Some(coll_name))));
+                }
+                if let [db_name] = self.state.selected() {
+                    return Ok(Some(Action::RequestDrop(db_name.clone(), None)));
+                }
+            }
+            KeyCode::Char('S') => {
+                if let Some((db_name, coll_name)) = self.selected_collection() {
+                    if ctx.select_collection_by_name(&db_name, &coll_name) {
+                        return Ok(Some(Action::SampleDocuments(db_name, coll_name)));
+                    }
+                }
+            }
             KeyCode::Enter | KeyCode::Char(' ') => {
                 let selected = self.state.selected();
                 if selected.is_empty() {
@@ -87,31 +378,27 @@ impl Pane for DatabasesPane {
                     return Ok(Some(Action::Render));
                 }
 
-                let last_id = selected.last().unwrap();
                 // If ID contains ':', it's a collection: "db_name:coll_name"
-                if last_id.contains(':') {
-                    let parts: Vec<&str> = last_id.split(':').collect();
-                    if parts.len() == 2 {
-                        let db_name = parts[0];
-                        let coll_name = parts[1];
-
-                        // Find indices
-                        if let Some(db_idx) = ctx.databases.iter().position(|d| d.name == db_name) {
-                            if let Some(coll_idx) = ctx.databases[db_idx]
-                                .collections
-                                .iter()
-                                .position(|c| c.name == coll_name)
-                            {
-                                ctx.selected_db_index = Some(db_idx);
-                                ctx.selected_coll_index = Some(coll_idx);
-                                ctx.pagination.current_page = 0; // Reset pagination
-                                return Ok(Some(Action::RefreshDocuments));
-                            }
-                        }
+                if let Some((db_name, coll_name)) = self.selected_collection() {
+                    if ctx.select_collection_by_name(&db_name, &coll_name) {
+                        return Ok(Some(Action::RefreshDocuments));
                     }
                 } else {
-                    // It's a database, just toggle expand/collapse
+                    // It's a database, toggle expand/collapse and fetch counts when opening
+                    let db_name = selected.last().unwrap().clone();
+                    let is_empty = ctx
+                        .databases
+                        .iter()
+                        .any(|db| db.name == db_name && db.collections.is_empty());
+                    if is_empty {
+                        // Nothing to expand into
+                        ctx.set_status(format!("{} has no collections", db_name));
+                        return Ok(Some(Action::Render));
+                    }
                     self.state.toggle_selected();
+                    if self.state.opened().contains(&vec![db_name.clone()]) {
+                        return Ok(Some(Action::LoadCollectionMetadata(db_name)));
+                    }
                     return Ok(Some(Action::Render));
                 }
             }
@@ -120,33 +407,232 @@ impl Pane for DatabasesPane {
         Ok(None)
     }
 
+    fn handle_mouse_event(
+        &mut self,
+        mouse: MouseEvent,
+        _area: Rect,
+        ctx: &mut MongoContext,
+    ) -> Result<Option<Action>> {
+        if self.is_filtering {
+            return Ok(None);
+        }
+        if let Some(key) = scroll_key(&mouse) {
+            return self.handle_key_event(key, ctx);
+        }
+        if mouse.kind != MouseEventKind::Down(MouseButton::Left) {
+            return Ok(None);
+        }
+        let Some(clicked) = self
+            .state
+            .rendered_at(Position::new(mouse.column, mouse.row))
+            .map(<[String]>::to_vec)
+        else {
+            return Ok(None);
+        };
+        // Clicking the selected node again opens it, like Enter
+        if self.state.selected() == clicked.as_slice() {
+            return self.handle_key_event(KeyEvent::from(KeyCode::Enter), ctx);
+        }
+        self.state.select(clicked);
+        Ok(Some(Action::Render))
+    }
+
     fn draw(
         &mut self,
         f: &mut Frame,
         area: Rect,
         is_active: bool,
-        _ctx: &MongoContext,
+        ctx: &MongoContext,
     ) -> Result<()> {
         // Show subset
         let shortcuts_str = "Space/Enter: Expand/Select";
 
+        let mut title = if self.is_filtering || !self.filter.is_empty() {
+            let cursor = if self.is_filtering { "_" } else { "" };
+            format!("[2] Databases /{}{}", self.filter, cursor)
+        } else {
+            "[2] Databases".to_string()
+        };
+        if let Some(spinner) = ctx.spinner(LoadingKind::Databases) {
+            title.push_str(&format!(" {}", spinner));
+        }
+
         let block = Block::default()
-            .title("[2] Databases")
-            .title_bottom(Line::from(shortcuts_str).alignment(Alignment::Center))
+            .title(ctx.theme.title(is_active, &title))
+            .title_bottom(
+                Line::from(shortcuts_str)
+                    .style(ctx.theme.hint(is_active))
+                    .alignment(Alignment::Center),
+            )
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(if is_active {
-                Style::default().fg(Color::Yellow)
-            } else {
-                Style::default()
-            });
+            .border_style(ctx.theme.border(is_active));
+
+        if ctx.databases_loaded && ctx.databases.is_empty() {
+            let empty = Paragraph::new("Connected — no databases visible (check permissions)")
+                .style(ctx.theme.muted())
+                .wrap(Wrap { trim: true })
+                .block(block);
+            f.render_widget(empty, area);
+            return Ok(());
+        }
+
+        let inner = block.inner(area);
+        if self.label_width != inner.width as usize {
+            self.label_width = inner.width as usize;
+            self.rebuild_tree_items(ctx);
+        }
+        f.render_widget(block, area);
+
+        let (tree_area, detail) = match self.truncated_selection() {
+            Some(full) => {
+                let [tree_area, detail_area] = split_detail(inner, &full);
+                (tree_area, Some((full, detail_area)))
+            }
+            None => (inner, None),
+        };
 
         let tree = Tree::new(&self.tree_items)
             .expect("all item identifiers are unique")
-            .block(block)
-            .highlight_style(Style::default().fg(Color::Black).bg(Color::Blue));
+            .highlight_style(ctx.theme.highlight())
+            .highlight_symbol(ctx.theme.highlight_symbol());
 
-        f.render_stateful_widget(tree, area, &mut self.state);
+        f.render_stateful_widget(tree, tree_area, &mut self.state);
+        if let Some((full, detail_area)) = detail {
+            f.render_widget(
+                Paragraph::new(full)
+                    .style(ctx.theme.muted())
+                    .wrap(Wrap { trim: false }),
+                detail_area,
+            );
+        }
         Ok(())
     }
 }
+
+/// Columns taken by the tree's indentation and expand symbol before a name.
+const DATABASE_INDENT: usize = 2;
+const COLLECTION_INDENT: usize = 4;
+
+fn name_matches(name: &str, filter: &str) -> bool {
+    fuzzy_match(name, filter).is_some()
+}
+
+/// Character positions in `name` where the characters of `filter` appear in
+/// order, ignoring case, taking the earliest of each. `None` when they don't
+/// all appear.
+fn fuzzy_match(name: &str, filter: &str) -> Option<Vec<usize>> {
+    let mut wanted = filter.chars().flat_map(char::to_lowercase).peekable();
+    let mut positions = vec![];
+    for (i, c) in name.chars().enumerate() {
+        let Some(&next) = wanted.peek() else {
+            break;
+        };
+        if c.to_lowercase().eq(std::iter::once(next)) {
+            positions.push(i);
+            wanted.next();
+        }
+    }
+    wanted.peek().is_none().then_some(positions)
+}
+
+/// Splits `name` into spans with the characters matched by `filter`
+/// highlighted. The name may be shortened, so it is matched on its own.
+fn highlight_match(name: &str, filter: &str, style: Style) -> Vec<Span<'static>> {
+    let positions = fuzzy_match(name, filter).unwrap_or_default();
+    let mut spans: Vec<Span<'static>> = vec![];
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (i, c) in name.chars().enumerate() {
+        let matched = positions.contains(&i);
+        if matched != run_matched && !run.is_empty() {
+            let text = std::mem::take(&mut run);
+            spans.push(if run_matched {
+                Span::styled(text, style)
+            } else {
+                Span::raw(text)
+            });
+        }
+        run_matched = matched;
+        run.push(c);
+    }
+    spans.push(if run_matched {
+        Span::styled(run, style)
+    } else {
+        Span::raw(run)
+    });
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use mongo_core::{CollectionInfo, DatabaseInfo};
+
+    use super::*;
+
+    fn database(name: &str, collections: &[&str]) -> DatabaseInfo {
+        DatabaseInfo {
+            name: name.to_string(),
+            collections: collections
+                .iter()
+                .map(|c| CollectionInfo {
+                    name: c.to_string(),
+                    kind: None,
+                    capped: false,
+                    estimated_count: None,
+                })
+                .collect(),
+            stats: None,
+        }
+    }
+
+    #[test]
+    fn refresh_keeps_open_databases_and_prunes_vanished_ones() {
+        let mut pane = DatabasesPane::new(PaneId::new());
+        pane.state.open(vec!["shop".to_string()]);
+        pane.state.open(vec!["old".to_string()]);
+        pane.state
+            .select(vec!["old".to_string(), "old:logs".to_string()]);
+
+        let mut ctx = MongoContext::new();
+        ctx.databases = vec![database("shop", &["orders"]), database("crm", &[])];
+        pane.prune_missing(&ctx);
+
+        let opened: Vec<&Vec<String>> = pane.state.opened().iter().collect();
+        assert_eq!(opened, [&vec!["shop".to_string()]]);
+        assert!(pane.state.selected().is_empty());
+    }
+
+    #[test]
+    fn refresh_keeps_a_selected_collection_that_still_exists() {
+        let mut pane = DatabasesPane::new(PaneId::new());
+        let selected = vec!["shop".to_string(), "shop:orders".to_string()];
+        pane.state.select(selected.clone());
+
+        let mut ctx = MongoContext::new();
+        ctx.databases = vec![database("shop", &["orders"])];
+        pane.prune_missing(&ctx);
+
+        assert_eq!(pane.state.selected(), selected.as_slice());
+    }
+
+    #[test]
+    fn clearing_the_filter_restores_the_open_databases() {
+        let mut pane = DatabasesPane::new(PaneId::new());
+        pane.state.open(vec!["shop".to_string()]);
+
+        let mut ctx = MongoContext::new();
+        ctx.databases = vec![
+            database("shop", &["orders"]),
+            database("crm", &["contacts"]),
+        ];
+        pane.filter = "contacts".to_string();
+        pane.rebuild_tree_items(&ctx);
+        assert!(pane.state.opened().contains(&vec!["crm".to_string()]));
+
+        pane.filter.clear();
+        pane.rebuild_tree_items(&ctx);
+        let opened: Vec<&Vec<String>> = pane.state.opened().iter().collect();
+        assert_eq!(opened, [&vec!["shop".to_string()]]);
+    }
+}
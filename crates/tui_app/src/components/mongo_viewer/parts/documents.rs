@@ -1,5 +1,5 @@
 use color_eyre::eyre::Result;
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
 use ratatui::{
     prelude::*,
     widgets::{
@@ -7,19 +7,55 @@ use ratatui::{
     },
 };
 // use serde_json::Value;
-use std::collections::HashSet;
+use mongo_core::bson::{doc, Bson, Document};
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
 
-use super::super::{context::MongoContext, defs::ViewMode, pane_id::PaneId, registry::Pane};
+use super::super::{
+    context::MongoContext,
+    defs::{LoadingKind, ViewMode},
+    format::{
+        display_cell, escape_regex, format_bson_copy, format_cell, format_count, get_nested,
+        object_id_created, CREATED_COLUMN,
+    },
+    pane_id::PaneId,
+    registry::Pane,
+};
+use super::{clicked_line, scroll_key};
 use crate::action::Action;
 
+/// Sort used by the "newest first" toggle.
+const REVERSE_NATURAL_SORT: &str = r#"{ "$natural": -1 }"#;
+/// How much `+`/`-` grow or shrink the page size.
+const PAGE_SIZE_STEP: isize = 10;
+/// How long documents inserted while watching stay marked.
+const LIVE_INSERT_MARK: Duration = Duration::from_secs(3);
+/// Narrowest a table column gets; columns that don't fit scroll into view.
+const MIN_COLUMN_WIDTH: u16 = 16;
+
 pub struct DocumentsPane {
     id: PaneId,
     view_mode: ViewMode,
     table_state: TableState,
     list_state: ListState,
     selected_column_index: usize,
+    // Leftmost column drawn when not all of them fit
+    first_visible_column: usize,
     visible_fields: Vec<String>,
     all_fields: Vec<String>,
+    // `_id` of the selected row, used to find it again after a reload
+    selected_id: Option<Bson>,
+    // Case-insensitive search over the visible cells of the loaded page
+    search: String,
+    is_searching: bool,
+    search_matches: Vec<usize>,
+    // Columns and view mode picked per `(db, collection)` this session
+    remembered_fields: HashMap<(String, String), Vec<String>>,
+    remembered_view_modes: HashMap<(String, String), ViewMode>,
+    // `_id`s of documents the change stream inserted, and when
+    live_inserts: Vec<(Bson, Instant)>,
     // expanded_docs: HashMap<usize, bool>,
 }
 
@@ -31,20 +67,207 @@ impl DocumentsPane {
             table_state: TableState::default(),
             list_state: ListState::default(),
             selected_column_index: 0,
+            first_visible_column: 0,
             visible_fields: vec!["_id".to_string()],
             all_fields: vec![],
+            selected_id: None,
+            search: String::new(),
+            is_searching: false,
+            search_matches: vec![],
+            remembered_fields: HashMap::new(),
+            remembered_view_modes: HashMap::new(),
+            live_inserts: vec![],
             // expanded_docs: HashMap::new(),
         }
     }
 
-    fn toggle_view_mode(&mut self) {
+    /// Recomputes the selectable columns from the collection schema plus the
+    /// keys of the loaded documents, and resets the visible ones to those
+    /// picked earlier for this collection, or to the default.
+    fn reset_fields(&mut self, ctx: &MongoContext) {
+        let remembered = namespace_key(ctx).and_then(|key| self.remembered_fields.get(&key));
+        // `_id` can still be toggled on from the field selector when hidden by config
+        self.visible_fields = if let Some(fields) = remembered {
+            fields.clone()
+        } else if ctx.config.config.show_id_column {
+            vec!["_id".to_string()]
+        } else {
+            Vec::new()
+        };
+
+        let mut fields: HashSet<String> = ctx.schema.iter().map(|(path, _)| path.clone()).collect();
+        for doc in ctx.documents.iter().take(20) {
+            for k in doc.keys() {
+                fields.insert(k.clone());
+            }
+        }
+        let mut sorted_fields: Vec<String> = fields.into_iter().collect();
+        sorted_fields.sort();
+        self.all_fields = sorted_fields;
+
+        // Add a few more top-level fields to visible by default if available
+        for field in self.all_fields.iter() {
+            if remembered.is_none()
+                && field != "_id"
+                && !field.contains('.')
+                && self.visible_fields.len() < 5
+            {
+                self.visible_fields.push(field.clone());
+            }
+        }
+        // Never leave the table without columns, even when `_id` is hidden
+        if self.visible_fields.is_empty() && self.all_fields.iter().any(|f| f == "_id") {
+            self.visible_fields.push("_id".to_string());
+        }
+        // Offer the ObjectId creation time as an optional column
+        if ctx
+            .documents
+            .iter()
+            .any(|doc| doc.get_object_id("_id").is_ok())
+        {
+            self.all_fields.push(CREATED_COLUMN.to_string());
+        }
+        // Another collection may show fewer columns
+        self.selected_column_index = self
+            .selected_column_index
+            .min(self.visible_fields.len().saturating_sub(1));
+    }
+
+    /// Recomputes the rows with a visible cell containing the search term.
+    /// Only the loaded page is searched; the server is not queried.
+    fn update_search_matches(&mut self, ctx: &MongoContext) {
+        let term = self.search.to_lowercase();
+        self.search_matches = if term.is_empty() {
+            vec![]
+        } else {
+            ctx.documents
+                .iter()
+                .enumerate()
+                .filter(|(_, doc)| {
+                    self.visible_fields
+                        .iter()
+                        .any(|field| format_cell(doc, field).to_lowercase().contains(&term))
+                })
+                .map(|(i, _)| i)
+                .collect()
+        };
+    }
+
+    /// Selects the next (or previous) matching row, wrapping around. With
+    /// `inclusive` the selected row itself counts as the next match.
+    fn jump_to_match(&mut self, forward: bool, inclusive: bool, ctx: &mut MongoContext) {
+        let current = self.table_state.selected();
+        let target = if forward {
+            self.search_matches
+                .iter()
+                .find(|&&i| current.is_none_or(|c| i > c || (inclusive && i == c)))
+                .or(self.search_matches.first())
+        } else {
+            self.search_matches
+                .iter()
+                .rev()
+                .find(|&&i| current.is_none_or(|c| i < c))
+                .or(self.search_matches.last())
+        };
+        if let Some(&row) = target {
+            self.select_row(Some(row), ctx);
+        }
+    }
+
+    /// Document shown on the given line below the table header or at the top
+    /// of the JSON list.
+    fn row_at_line(&self, line: usize, ctx: &MongoContext) -> Option<usize> {
+        if self.view_mode == ViewMode::Table {
+            let row = self.table_state.offset() + line;
+            return (row < ctx.documents.len()).then_some(row);
+        }
+        // JSON items span as many lines as their pretty-printed document
+        let mut top = 0;
+        for (i, doc) in ctx
+            .documents
+            .iter()
+            .enumerate()
+            .skip(self.list_state.offset())
+        {
+            top += serde_json::to_string_pretty(doc).map_or(1, |json| json.lines().count());
+            if line < top {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    /// Selects a row in both the table and the JSON list and remembers its `_id`.
+    fn select_row(&mut self, idx: Option<usize>, ctx: &mut MongoContext) {
+        self.table_state.select(idx);
+        self.list_state.select(idx);
+        ctx.selected_document = idx;
+        self.selected_id = idx
+            .and_then(|i| ctx.documents.get(i))
+            .and_then(|doc| doc.get("_id").cloned());
+    }
+
+    /// Puts a document inserted into the watched collection at the top of
+    /// the page, keeping the page at its size and the same row selected.
+    fn apply_change_event(&mut self, event: &Document, ctx: &mut MongoContext) {
+        let Ok(ns) = event.get_document("ns") else {
+            return;
+        };
+        let same_collection = ctx
+            .selected_namespace()
+            .is_some_and(|(db, coll)| ns.get_str("db") == Ok(db) && ns.get_str("coll") == Ok(coll));
+        if !same_collection || event.get_str("operationType") != Ok("insert") {
+            return;
+        }
+        let Ok(doc) = event.get_document("fullDocument") else {
+            return;
+        };
+        if let Some(id) = doc.get("_id") {
+            self.live_inserts
+                .retain(|(_, at)| at.elapsed() < LIVE_INSERT_MARK);
+            self.live_inserts.push((id.clone(), Instant::now()));
+        }
+        ctx.documents.insert(0, doc.clone());
+        ctx.documents.truncate(ctx.rows_per_page());
+        if let Some(total) = ctx.pagination.total_count.as_mut() {
+            *total += 1;
+        }
+
+        let selected = self.selected_id.as_ref().and_then(|id| {
+            ctx.documents
+                .iter()
+                .position(|doc| doc.get("_id") == Some(id))
+        });
+        self.select_row(selected.or(Some(0)), ctx);
+        self.update_search_matches(ctx);
+    }
+
+    fn is_live_insert(&self, doc: &Document) -> bool {
+        doc.get("_id").is_some_and(|id| {
+            self.live_inserts
+                .iter()
+                .any(|(live, at)| live == id && at.elapsed() < LIVE_INSERT_MARK)
+        })
+    }
+
+    fn toggle_view_mode(&mut self, ctx: &MongoContext) {
         self.view_mode = match self.view_mode {
             ViewMode::Table => ViewMode::Json,
             ViewMode::Json => ViewMode::Table,
         };
+        if let Some(key) = namespace_key(ctx) {
+            self.remembered_view_modes
+                .insert(key, self.view_mode.clone());
+        }
     }
 }
 
+/// Key under which column and view preferences are remembered.
+fn namespace_key(ctx: &MongoContext) -> Option<(String, String)> {
+    ctx.selected_namespace()
+        .map(|(db, coll)| (db.to_string(), coll.to_string()))
+}
+
 impl Pane for DocumentsPane {
     fn id(&self) -> PaneId {
         self.id
@@ -55,62 +278,92 @@ impl Pane for DocumentsPane {
     }
 
     fn get_shortcuts(&self) -> Vec<(&'static str, &'static str)> {
-        let mut s = vec![("Enter", "View"), ("j/k", "Nav"), ("n/b", "Page")];
+        let mut s = vec![
+            ("Enter", "View"),
+            ("j/k", "Nav"),
+            (":", "Go to Row"),
+            ("n/b", "Page"),
+            ("/", "Search Page"),
+            ("g/G/#", "First/Last/Go to Page"),
+        ];
+        if !self.search.is_empty() {
+            s.push(("n/N", "Next/Prev Match"));
+        }
         if self.view_mode == ViewMode::Table {
             s.push(("h/l", "Columns"));
             s.push(("y/Y", "Copy ID/Doc"));
             s.push(("p/P", "Copy Val/Key"));
             s.push(("f", "Fields"));
+            s.push(("C", "Copy Projection"));
+            s.push(("~", "Regex Filter"));
+            s.push(("D", "Distinct Values"));
         } else {
             s.push(("y/Y", "Copy ID/Doc"));
         }
+        s.push(("Space", "Detail Drawer"));
+        s.push(("a/e/d", "Insert/Edit/Delete"));
+        s.push(("U", "Update Many"));
+        s.push(("R", "Reverse Order"));
+        s.push(("i", "Indexes"));
+        s.push(("x", "Explain"));
+        s.push(("E", "Export"));
+        s.push(("+/-", "Page Size"));
         s.push(("v", "Toggle View"));
+        s.push(("r", "Auto-refresh"));
+        s.push(("w", "Watch Changes"));
         s
     }
 
+    fn clear_selection(&mut self, ctx: &mut MongoContext) {
+        self.select_row(None, ctx);
+    }
+
+    fn captures_input(&self) -> bool {
+        self.is_searching
+    }
+
     fn update(&mut self, action: Action, ctx: &mut MongoContext) -> Result<Option<Action>> {
         match action {
-            Action::DocumentsLoaded(_, _) => {
-                // Reset visible fields to default
-                self.visible_fields = vec!["_id".to_string()];
-
-                // Update all_fields based on keys in the first few documents
-                let mut fields = HashSet::new();
-                for doc in ctx.documents.iter().take(20) {
-                    for k in doc.keys() {
-                        fields.insert(k.clone());
-                    }
-                }
-                let mut sorted_fields: Vec<String> = fields.into_iter().collect();
-                sorted_fields.sort();
-                self.all_fields = sorted_fields;
-
-                // Add a few more fields to visible by default if available
-                for field in self.all_fields.iter() {
-                    if field != "_id" && self.visible_fields.len() < 5 {
-                        self.visible_fields.push(field.clone());
-                    }
-                }
-
-                // Reset selection
-                self.table_state.select(if !ctx.documents.is_empty() {
-                    Some(0)
-                } else {
-                    None
+            Action::DocumentsLoaded(..) => {
+                self.reset_fields(ctx);
+                self.view_mode = namespace_key(ctx)
+                    .and_then(|key| self.remembered_view_modes.get(&key).cloned())
+                    .unwrap_or(ViewMode::Table);
+                self.update_search_matches(ctx);
+
+                // Keep the previously selected document if it is still there
+                let previous = self.selected_id.as_ref().and_then(|id| {
+                    ctx.documents
+                        .iter()
+                        .position(|doc| doc.get("_id") == Some(id))
                 });
-                self.list_state.select(if !ctx.documents.is_empty() {
+                let idx = previous.or(if !ctx.documents.is_empty() {
                     Some(0)
                 } else {
                     None
                 });
+                self.select_row(idx, ctx);
+            }
+            Action::SchemaLoaded(_) => self.reset_fields(ctx),
+            Action::ChangeEvent(event) => self.apply_change_event(&event, ctx),
+            // Out-of-range rows land on the last one
+            Action::JumpToRow(row) if !ctx.documents.is_empty() => {
+                self.select_row(Some(row.min(ctx.documents.len() - 1)), ctx);
             }
             Action::ToggleViewMode => {
-                self.toggle_view_mode();
+                self.toggle_view_mode(ctx);
                 return Ok(Some(Action::Render));
             }
             Action::UpdateVisibleFields(fields) => {
+                if let Some(key) = namespace_key(ctx) {
+                    self.remembered_fields.insert(key, fields.clone());
+                }
                 self.visible_fields = fields;
-                self.selected_column_index = 0; // Reset to avoid out of bounds
+                self.selected_column_index = self
+                    .selected_column_index
+                    .min(self.visible_fields.len().saturating_sub(1));
+                self.first_visible_column = 0;
+                self.update_search_matches(ctx);
                 return Ok(Some(Action::Render));
             }
             _ => {}
@@ -123,17 +376,130 @@ impl Pane for DocumentsPane {
         key: KeyEvent,
         ctx: &mut MongoContext,
     ) -> Result<Option<Action>> {
+        if self.is_searching {
+            match key.code {
+                KeyCode::Esc => {
+                    self.is_searching = false;
+                    self.search.clear();
+                }
+                KeyCode::Enter => {
+                    self.is_searching = false;
+                    self.jump_to_match(true, true, ctx);
+                }
+                KeyCode::Backspace => {
+                    self.search.pop();
+                }
+                KeyCode::Char(c) => self.search.push(c),
+                _ => return Ok(None),
+            }
+            self.update_search_matches(ctx);
+            return Ok(Some(Action::Render));
+        }
+
         match key.code {
+            KeyCode::Char('/') => {
+                self.is_searching = true;
+                return Ok(Some(Action::Render));
+            }
+            KeyCode::Esc if ctx.query_task.is_some() => {
+                ctx.cancel_query();
+                ctx.set_status("Query cancelled");
+                return Ok(Some(Action::Render));
+            }
+            KeyCode::Esc if !self.search.is_empty() => {
+                self.search.clear();
+                self.update_search_matches(ctx);
+                return Ok(Some(Action::Render));
+            }
+            KeyCode::Char('n') if !self.search.is_empty() => {
+                self.jump_to_match(true, false, ctx);
+                return Ok(Some(Action::Render));
+            }
+            KeyCode::Char('N') if !self.search.is_empty() => {
+                self.jump_to_match(false, false, ctx);
+                return Ok(Some(Action::Render));
+            }
             KeyCode::Char('v') => {
-                self.toggle_view_mode();
+                self.toggle_view_mode(ctx);
+                return Ok(Some(Action::Render));
+            }
+            KeyCode::Char(' ') => {
+                ctx.show_detail_drawer = !ctx.show_detail_drawer;
+                return Ok(Some(Action::Render));
+            }
+            KeyCode::Char('r') => {
+                ctx.auto_refresh = match ctx.auto_refresh {
+                    Some(_) => None,
+                    None => Some(Instant::now()),
+                };
+                ctx.set_status(if ctx.auto_refresh.is_some() {
+                    format!(
+                        "Auto-refresh every {}s",
+                        ctx.config.config.auto_refresh_secs.max(1)
+                    )
+                } else {
+                    "Auto-refresh off".to_string()
+                });
                 return Ok(Some(Action::Render));
             }
+            KeyCode::Char('w') => {
+                return Ok(Some(if ctx.watching == namespace_key(ctx) {
+                    Action::StopWatch
+                } else {
+                    Action::StartWatch
+                }));
+            }
+            KeyCode::Char(':') => return Ok(Some(Action::OpenJumpToRow)),
+            KeyCode::Char('a') => return Ok(Some(Action::OpenInsertDocument)),
+            KeyCode::Char('U') => return Ok(Some(Action::OpenBulkUpdate)),
+            KeyCode::Char('e') => {
+                if let Some(row) = self.table_state.selected() {
+                    return Ok(Some(Action::OpenDocumentEditor(row)));
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Some(row) = self.table_state.selected() {
+                    return Ok(Some(Action::RequestDeleteDocument(row)));
+                }
+            }
+            KeyCode::Char('+') => {
+                ctx.adjust_page_size(PAGE_SIZE_STEP);
+                return Ok(Some(Action::RefreshDocuments));
+            }
+            KeyCode::Char('-') => {
+                ctx.adjust_page_size(-PAGE_SIZE_STEP);
+                return Ok(Some(Action::RefreshDocuments));
+            }
+            KeyCode::Char('R') => {
+                let current = ctx.sort_input.lines().join("");
+                if current.trim() == REVERSE_NATURAL_SORT {
+                    ctx.set_sort("");
+                    ctx.set_status("Default order");
+                } else {
+                    ctx.set_sort(REVERSE_NATURAL_SORT);
+                    ctx.set_status("Newest first");
+                }
+                ctx.pagination.current_page = 0;
+                return Ok(Some(Action::RefreshDocuments));
+            }
             KeyCode::Char('n') => {
                 return Ok(Some(Action::NextPage));
             }
             KeyCode::Char('b') => {
                 return Ok(Some(Action::PreviousPage));
             }
+            KeyCode::Char('g') => return Ok(Some(Action::GoToPage(0))),
+            KeyCode::Char('G') => match ctx.page_count() {
+                Some(pages) => return Ok(Some(Action::GoToPage(pages - 1))),
+                None => {
+                    ctx.set_status("Page count is still loading");
+                    return Ok(Some(Action::Render));
+                }
+            },
+            KeyCode::Char('#') => return Ok(Some(Action::OpenGoToPage)),
+            KeyCode::Char('E') => {
+                return Ok(Some(Action::OpenExport(self.visible_fields.clone())));
+            }
             KeyCode::Char('f') => {
                 return Ok(Some(Action::OpenFieldSelector(
                     self.all_fields.clone(),
@@ -153,8 +519,7 @@ impl Pane for DocumentsPane {
                         }
                         None => 0,
                     };
-                    self.table_state.select(Some(i));
-                    self.list_state.select(Some(i));
+                    self.select_row(Some(i), ctx);
                     return Ok(Some(Action::Render));
                 }
             }
@@ -171,22 +536,22 @@ impl Pane for DocumentsPane {
                         }
                         None => 0,
                     };
-                    self.table_state.select(Some(i));
-                    self.list_state.select(Some(i));
+                    self.select_row(Some(i), ctx);
                     return Ok(Some(Action::Render));
                 }
             }
-            KeyCode::Left | KeyCode::Char('h') if self.view_mode == ViewMode::Table => {
-                if self.selected_column_index > 0 {
-                    self.selected_column_index -= 1;
-                    return Ok(Some(Action::Render));
-                }
+            KeyCode::Left | KeyCode::Char('h')
+                if self.view_mode == ViewMode::Table && self.selected_column_index > 0 =>
+            {
+                self.selected_column_index -= 1;
+                return Ok(Some(Action::Render));
             }
-            KeyCode::Right | KeyCode::Char('l') if self.view_mode == ViewMode::Table => {
-                if self.selected_column_index < self.visible_fields.len().saturating_sub(1) {
-                    self.selected_column_index += 1;
-                    return Ok(Some(Action::Render));
-                }
+            KeyCode::Right | KeyCode::Char('l')
+                if self.view_mode == ViewMode::Table
+                    && self.selected_column_index < self.visible_fields.len().saturating_sub(1) =>
+            {
+                self.selected_column_index += 1;
+                return Ok(Some(Action::Render));
             }
             KeyCode::Char('y') => {
                 if let Some(idx) = self.table_state.selected() {
@@ -198,9 +563,8 @@ impl Pane for DocumentsPane {
                         } else {
                             String::new()
                         };
-                        if let Some(cb) = &mut ctx.clipboard {
-                            let _ = cb.set_text(val);
-                        }
+                        ctx.copy(val, "_id");
+                        return Ok(Some(Action::Render));
                     }
                 }
             }
@@ -208,21 +572,70 @@ impl Pane for DocumentsPane {
                 if let Some(idx) = self.table_state.selected() {
                     if let Some(doc) = ctx.documents.get(idx) {
                         if let Ok(json) = serde_json::to_string_pretty(doc) {
-                            if let Some(cb) = &mut ctx.clipboard {
-                                let _ = cb.set_text(json);
-                            }
+                            ctx.copy(json, "document");
+                            return Ok(Some(Action::Render));
                         }
                     }
                 }
             }
+            KeyCode::Char('~') if self.view_mode == ViewMode::Table => {
+                let selected = self
+                    .table_state
+                    .selected()
+                    .and_then(|idx| ctx.documents.get(idx))
+                    .zip(self.visible_fields.get(self.selected_column_index));
+                if let Some((doc, field)) = selected {
+                    let Some(value) = get_nested(doc, field).and_then(Bson::as_str) else {
+                        ctx.set_status("Regex filter needs a string cell");
+                        return Ok(Some(Action::Render));
+                    };
+                    let filter = Bson::Document(doc! {
+                        field.as_str(): { "$regex": escape_regex(value), "$options": "i" }
+                    })
+                    .into_relaxed_extjson()
+                    .to_string();
+                    ctx.set_filter(&filter);
+                    ctx.pagination.current_page = 0;
+                    return Ok(Some(Action::RefreshDocuments));
+                }
+            }
+            KeyCode::Char('i') => return Ok(Some(Action::LoadIndexes)),
+            KeyCode::Char('x') => return Ok(Some(Action::ExplainQuery)),
+            KeyCode::Char('D') if self.view_mode == ViewMode::Table => {
+                match self.visible_fields.get(self.selected_column_index) {
+                    Some(field) if field == CREATED_COLUMN => {
+                        ctx.set_status("(created) is derived from _id, not a field");
+                        return Ok(Some(Action::Render));
+                    }
+                    Some(field) => return Ok(Some(Action::LoadDistinct(field.clone()))),
+                    None => {}
+                }
+            }
+            KeyCode::Char('C') if self.view_mode == ViewMode::Table => {
+                // Inclusion projection of the visible columns, in column order
+                let mut projection = Document::new();
+                for field in self.visible_fields.iter().filter(|f| *f != CREATED_COLUMN) {
+                    projection.insert(field.clone(), 1);
+                }
+                if !self.visible_fields.iter().any(|f| f == "_id") {
+                    projection.insert("_id", 0);
+                }
+                ctx.copy(projection.to_string(), "projection");
+                return Ok(Some(Action::Render));
+            }
             KeyCode::Char('p') if self.view_mode == ViewMode::Table => {
                 if let Some(idx) = self.table_state.selected() {
                     if let Some(doc) = ctx.documents.get(idx) {
                         if let Some(field) = self.visible_fields.get(self.selected_column_index) {
-                            let val = doc.get(field).map(|v| v.to_string()).unwrap_or_default();
-                            if let Some(cb) = &mut ctx.clipboard {
-                                let _ = cb.set_text(val);
-                            }
+                            let val = if field == CREATED_COLUMN {
+                                format_cell(doc, field)
+                            } else {
+                                get_nested(doc, field)
+                                    .map(format_bson_copy)
+                                    .unwrap_or_default()
+                            };
+                            ctx.copy(val, field);
+                            return Ok(Some(Action::Render));
                         }
                     }
                 }
@@ -231,37 +644,38 @@ impl Pane for DocumentsPane {
                 let selected_idx = self.table_state.selected();
                 if let Some(idx) = selected_idx {
                     if let Some(doc) = ctx.documents.get(idx) {
-                        if let Ok(json) = serde_json::to_string_pretty(doc) {
-                            // Extract ID for title
-                            let id_str = if let Ok(id) = doc.get_object_id("_id") {
-                                id.to_string()
-                            } else if let Some(id) = doc.get("_id") {
-                                id.to_string()
-                            } else {
-                                "?".to_string()
-                            };
+                        // Extract ID for title
+                        let id_str = if let Ok(id) = doc.get_object_id("_id") {
+                            id.to_string()
+                        } else if let Some(id) = doc.get("_id") {
+                            id.to_string()
+                        } else {
+                            "?".to_string()
+                        };
 
-                            let mut title_parts = vec![];
-                            if let Some(idx) = ctx.selected_connection {
-                                if let Some(conn) = ctx.connections.get(idx) {
-                                    title_parts.push(conn.name.as_str());
-                                }
+                        let mut title_parts = vec![];
+                        if let Some(idx) = ctx.selected_connection {
+                            if let Some(conn) = ctx.connections.get(idx) {
+                                title_parts.push(conn.name.as_str());
                             }
-                            if let Some(idx) = ctx.selected_db_index {
-                                if let Some(db) = ctx.databases.get(idx) {
-                                    title_parts.push(db.name.as_str());
-                                    if let Some(c_idx) = ctx.selected_coll_index {
-                                        if let Some(coll) = db.collections.get(c_idx) {
-                                            title_parts.push(coll.name.as_str());
-                                        }
+                        }
+                        if let Some(idx) = ctx.selected_db_index {
+                            if let Some(db) = ctx.databases.get(idx) {
+                                title_parts.push(db.name.as_str());
+                                if let Some(c_idx) = ctx.selected_coll_index {
+                                    if let Some(coll) = db.collections.get(c_idx) {
+                                        title_parts.push(coll.name.as_str());
                                     }
                                 }
                             }
-                            title_parts.push(&id_str);
-                            let title = title_parts.join(" / ");
-
-                            return Ok(Some(Action::OpenJsonPopup(json, title)));
                         }
+                        title_parts.push(&id_str);
+                        let mut title = title_parts.join(" / ");
+                        if let Some(created) = object_id_created(doc) {
+                            title.push_str(&format!(" (created {})", created));
+                        }
+
+                        return Ok(Some(Action::OpenDocumentTree(doc.clone(), title)));
                     }
                 }
             }
@@ -270,6 +684,37 @@ impl Pane for DocumentsPane {
         Ok(None)
     }
 
+    fn handle_mouse_event(
+        &mut self,
+        mouse: MouseEvent,
+        area: Rect,
+        ctx: &mut MongoContext,
+    ) -> Result<Option<Action>> {
+        if self.is_searching {
+            return Ok(None);
+        }
+        if let Some(key) = scroll_key(&mouse) {
+            return self.handle_key_event(key, ctx);
+        }
+        // The table header takes a line plus its bottom margin
+        let header = if self.view_mode == ViewMode::Table {
+            2
+        } else {
+            0
+        };
+        let Some(row) =
+            clicked_line(&mouse, area, header).and_then(|line| self.row_at_line(line, ctx))
+        else {
+            return Ok(None);
+        };
+        // Clicking the selected document again opens it, like Enter
+        if self.table_state.selected() == Some(row) {
+            return self.handle_key_event(KeyEvent::from(KeyCode::Enter), ctx);
+        }
+        self.select_row(Some(row), ctx);
+        Ok(Some(Action::Render))
+    }
+
     fn draw(
         &mut self,
         f: &mut Frame,
@@ -324,100 +769,196 @@ impl Pane for DocumentsPane {
             }
         }
 
+        if let Some(spinner) = ctx.spinner(LoadingKind::Documents) {
+            title.push_str(&format!(" {}", spinner));
+        }
+        if ctx.auto_refresh.is_some() {
+            title.push_str(&format!(
+                " ⟳ {}s",
+                ctx.config.config.auto_refresh_secs.max(1)
+            ));
+        }
+
+        if ctx.watching.is_some() && ctx.watching == namespace_key(ctx) {
+            title.push_str(" ● live");
+        }
+
+        if self.is_searching || !self.search.is_empty() {
+            let cursor = if self.is_searching { "_" } else { "" };
+            title = format!(
+                "{} /{}{} ({} matches)",
+                title,
+                self.search,
+                cursor,
+                self.search_matches.len()
+            );
+        }
+
         // View Mode
         let view_mode_str = match self.view_mode {
             ViewMode::Table => "Table",
             ViewMode::Json => "JSON",
         };
-        let view_title = format!(" View: {} ", view_mode_str);
+        let mut view_title = format!(" View: {} ", view_mode_str);
+
+        // Window of columns that fit at their minimum width, slid just enough
+        // to keep the active column in view
+        let inner_width = area
+            .width
+            .saturating_sub(2 + ctx.theme.highlight_symbol().len() as u16);
+        let fit = ((inner_width + 1) / (MIN_COLUMN_WIDTH + 1)).max(1) as usize;
+        let total_columns = self.visible_fields.len();
+        if self.selected_column_index < self.first_visible_column {
+            self.first_visible_column = self.selected_column_index;
+        } else if self.selected_column_index >= self.first_visible_column + fit {
+            self.first_visible_column = self.selected_column_index + 1 - fit;
+        }
+        self.first_visible_column = self
+            .first_visible_column
+            .min(total_columns.saturating_sub(fit));
+        let columns =
+            self.first_visible_column..(self.first_visible_column + fit).min(total_columns);
+        if self.view_mode == ViewMode::Table && columns.len() < total_columns {
+            view_title = format!(
+                " View: {} | cols {}-{}/{} ",
+                view_mode_str,
+                columns.start + 1,
+                columns.end,
+                total_columns
+            );
+        }
 
         // Doc Count
         let count_str = if let Some(total) = ctx.pagination.total_count {
-            let limit = ctx
-                .limit_input
-                .lines()
-                .join("")
-                .parse::<usize>()
-                .unwrap_or(10);
-            let total_pages = if limit > 0 {
-                (total as usize).div_ceil(limit)
+            let total_pages = ctx.page_count().unwrap_or(1);
+            // A limit below the page size hides the rest of each page
+            let trimmed = if ctx.rows_per_page() < ctx.pagination.page_size {
+                format!(" | limit {}", ctx.rows_per_page())
+            } else {
+                String::new()
+            };
+            let approx = if ctx.pagination.count_estimated {
+                "~"
             } else {
-                1
+                ""
             };
             format!(
-                " Page {}/{} | {} docs ",
+                " Page {}/{} | {}{} docs{} ",
                 ctx.pagination.current_page + 1,
                 total_pages,
-                total
+                approx,
+                format_count(total),
+                trimmed
             )
         } else {
             format!(" {} docs ", ctx.documents.len())
         };
 
+        // Position of the cursor within the current page
+        let position_str = match self.table_state.selected() {
+            Some(i) if !ctx.documents.is_empty() => {
+                format!(" row {} of {} ", i + 1, ctx.documents.len())
+            }
+            _ => String::new(),
+        };
+
         let block = Block::default()
-            .title(title)
+            .title(ctx.theme.title(is_active, &title))
             .title(Line::from(view_title).alignment(Alignment::Right))
-            .title_bottom(Line::from(shortcuts_str).alignment(Alignment::Center))
+            .title_bottom(Line::from(position_str).alignment(Alignment::Left))
+            .title_bottom(
+                Line::from(shortcuts_str)
+                    .style(ctx.theme.hint(is_active))
+                    .alignment(Alignment::Center),
+            )
             .title_bottom(Line::from(count_str).alignment(Alignment::Right))
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(if is_active {
-                Style::default().fg(Color::Yellow)
-            } else {
-                Style::default()
-            });
+            .border_style(ctx.theme.border(is_active));
 
         if self.view_mode == ViewMode::Table {
             // Draw Table
-            let header_cells = self.visible_fields.iter().enumerate().map(|(i, h)| {
-                let style = if i == self.selected_column_index && is_active {
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD)
-                } else {
-                    Style::default().fg(Color::Cyan)
+            let fields = &self.visible_fields[columns.clone()];
+            let header_cells = fields.iter().zip(columns.clone()).map(|(h, i)| {
+                let style = match (i == self.selected_column_index, is_active) {
+                    (true, true) => ctx.theme.accent(),
+                    (true, false) => ctx.theme.accent().add_modifier(Modifier::DIM),
+                    _ => ctx.theme.label(),
                 };
                 Cell::from(h.as_str()).style(style)
             });
             let header = Row::new(header_cells).height(1).bottom_margin(1);
 
-            let rows = ctx.documents.iter().map(|doc| {
-                let cells = self
-                    .visible_fields
+            // Width each Fill column gets, past the borders, the highlight
+            // symbol and the one-cell gaps between columns
+            let spacing = fields.len().saturating_sub(1) as u16;
+            let width = area
+                .width
+                .saturating_sub(2 + ctx.theme.highlight_symbol().len() as u16 + spacing)
+                as usize
+                / fields.len().max(1);
+            let group_digits = ctx.config.config.thousands_separators;
+
+            let rows = ctx.documents.iter().enumerate().map(|(i, doc)| {
+                let cells = fields
                     .iter()
-                    .map(|k| doc.get(k).map(|v| v.to_string()).unwrap_or_default());
-                Row::new(cells)
+                    .map(|k| display_cell(doc, k, width, group_digits));
+                let row = Row::new(cells);
+                if self.is_live_insert(doc) {
+                    row.style(ctx.theme.success())
+                } else if self.search_matches.contains(&i) {
+                    row.style(ctx.theme.accent())
+                } else {
+                    row
+                }
             });
 
-            // Widths
-            let width = 100 / self.visible_fields.len().max(1) as u16;
-            let constraints = vec![Constraint::Percentage(width); self.visible_fields.len()];
+            // Share the width evenly; the window guarantees the minimum
+            let constraints = vec![Constraint::Fill(1); fields.len()];
+
+            // The active cell stays marked, dimmed, while focus is elsewhere
+            let row_style = if is_active {
+                ctx.theme.highlight()
+            } else {
+                ctx.theme.selection()
+            };
 
             let table = Table::new(rows, constraints)
                 .header(header)
                 .block(block)
-                .row_highlight_style(Style::default().bg(Color::Blue));
+                .row_highlight_style(row_style)
+                .cell_highlight_style(ctx.theme.cell(is_active))
+                .highlight_symbol(ctx.theme.highlight_symbol());
 
+            self.table_state
+                .select_column(Some(self.selected_column_index - columns.start));
             f.render_stateful_widget(table, area, &mut self.table_state);
         } else {
             // Draw JSON List
             let items: Vec<ListItem> = ctx
                 .documents
                 .iter()
-                .map(|doc| {
+                .enumerate()
+                .map(|(i, doc)| {
                     // Prettified JSON view for list
                     let json = serde_json::to_string_pretty(doc).unwrap_or_default();
                     let lines: Vec<Line> = json
                         .lines()
                         .map(|line| Line::from(line.to_string()))
                         .collect();
-                    ListItem::new(lines)
+                    let item = ListItem::new(lines);
+                    if self.search_matches.contains(&i) {
+                        item.style(ctx.theme.accent())
+                    } else {
+                        item
+                    }
                 })
                 .collect();
 
             let list = List::new(items)
                 .block(block)
-                .highlight_style(Style::default().bg(Color::Blue));
+                .highlight_style(ctx.theme.highlight())
+                .highlight_symbol(ctx.theme.highlight_symbol());
 
             f.render_stateful_widget(list, area, &mut self.list_state);
         }
@@ -2,3 +2,56 @@ pub mod connections;
 pub mod databases;
 pub mod documents;
 pub mod query;
+pub mod schema;
+
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Constraint, Layout, Margin, Position, Rect};
+
+/// Shortens `name` to at most `max` characters by replacing its middle with
+/// `…`, so both the prefix and the suffix stay recognizable.
+pub fn ellipsize(name: &str, max: usize) -> String {
+    let len = name.chars().count();
+    if len <= max || max == 0 {
+        return name.to_string();
+    }
+    let keep = max - 1;
+    let head = keep.div_ceil(2);
+    let tail = keep - head;
+    let mut out: String = name.chars().take(head).collect();
+    out.push('…');
+    out.extend(name.chars().skip(len - tail));
+    out
+}
+
+/// Splits a pane's inner area to make room at the bottom for `detail`
+/// wrapped over up to three lines.
+pub fn split_detail(inner: Rect, detail: &str) -> [Rect; 2] {
+    let width = inner.width.max(1) as usize;
+    let lines = detail.chars().count().div_ceil(width).clamp(1, 3) as u16;
+    Layout::vertical([Constraint::Min(0), Constraint::Length(lines)]).areas(inner)
+}
+
+/// The arrow key a wheel scroll stands for, so panes can reuse their j/k
+/// handling.
+pub fn scroll_key(mouse: &MouseEvent) -> Option<KeyEvent> {
+    match mouse.kind {
+        MouseEventKind::ScrollDown => Some(KeyEvent::from(KeyCode::Down)),
+        MouseEventKind::ScrollUp => Some(KeyEvent::from(KeyCode::Up)),
+        _ => None,
+    }
+}
+
+/// Line under a left click inside a bordered pane drawn at `area`, counted
+/// from the first line below `header`. `None` for other events and for
+/// clicks on the border or the header.
+pub fn clicked_line(mouse: &MouseEvent, area: Rect, header: u16) -> Option<usize> {
+    if mouse.kind != MouseEventKind::Down(MouseButton::Left) {
+        return None;
+    }
+    let inner = area.inner(Margin::new(1, 1));
+    let top = inner.y + header;
+    if !inner.contains(Position::new(mouse.column, mouse.row)) || mouse.row < top {
+        return None;
+    }
+    Some((mouse.row - top) as usize)
+}
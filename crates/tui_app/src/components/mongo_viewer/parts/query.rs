@@ -1,5 +1,5 @@
 use color_eyre::eyre::Result;
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     prelude::*,
     widgets::{Block, BorderType, Borders, Paragraph},
@@ -28,17 +28,44 @@ impl Pane for QueryPane {
     }
 
     fn get_shortcuts(&self) -> Vec<(&'static str, &'static str)> {
-        vec![("Enter", "Edit")]
+        vec![
+            ("Enter", "Edit"),
+            ("Ctrl-x", "Reset"),
+            ("y", "Copy FindOptions"),
+            ("a", "Aggregate"),
+            ("x", "Explain"),
+            ("H", "History"),
+            ("s", "Saved Queries"),
+        ]
     }
 
     fn handle_key_event(
         &mut self,
         key: KeyEvent,
-        _ctx: &mut MongoContext,
+        ctx: &mut MongoContext,
     ) -> Result<Option<Action>> {
-        if key.code == KeyCode::Enter {
-            // Signal to open the Query Builder popup
-            return Ok(Some(Action::OpenQueryBuilder));
+        match key.code {
+            KeyCode::Enter => {
+                // Signal to open the Query Builder popup
+                return Ok(Some(Action::OpenQueryBuilder));
+            }
+            KeyCode::Char('x') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                ctx.reset_query();
+                ctx.set_status("Query reset");
+                return Ok(Some(Action::RefreshDocuments));
+            }
+            KeyCode::Char('a') => return Ok(Some(Action::OpenAggregationBuilder)),
+            KeyCode::Char('x') => return Ok(Some(Action::ExplainQuery)),
+            KeyCode::Char('H') => return Ok(Some(Action::OpenQueryHistory)),
+            KeyCode::Char('s') => return Ok(Some(Action::OpenSavedQueries)),
+            KeyCode::Char('y') => {
+                let options = ctx.build_find_options();
+                if let Ok(json) = serde_json::to_string_pretty(&options) {
+                    ctx.copy(json, "FindOptions");
+                }
+                return Ok(Some(Action::Render));
+            }
+            _ => {}
         }
         Ok(None)
     }
@@ -58,15 +85,15 @@ impl Pane for QueryPane {
             .join(" | ");
 
         let block = Block::default()
-            .title("[3] Query")
-            .title_bottom(Line::from(shortcuts_str).alignment(Alignment::Center))
+            .title(ctx.theme.title(is_active, "[3] Query"))
+            .title_bottom(
+                Line::from(shortcuts_str)
+                    .style(ctx.theme.hint(is_active))
+                    .alignment(Alignment::Center),
+            )
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(if is_active {
-                Style::default().fg(Color::Yellow)
-            } else {
-                Style::default()
-            });
+            .border_style(ctx.theme.border(is_active));
 
         // Display current query summary
         let filter_line = ctx.query_input.lines().join("");
@@ -75,7 +102,7 @@ impl Pane for QueryPane {
 
         let text = vec![
             Line::from(vec![
-                Span::styled("Filter: ", Style::default().fg(Color::Cyan)),
+                Span::styled("Filter: ", ctx.theme.label()),
                 Span::raw(if filter_line.is_empty() {
                     "{}"
                 } else {
@@ -83,16 +110,16 @@ impl Pane for QueryPane {
                 }),
             ]),
             Line::from(vec![
-                Span::styled("Sort: ", Style::default().fg(Color::Cyan)),
+                Span::styled("Sort: ", ctx.theme.label()),
                 Span::raw(if sort_line.is_empty() {
                     "{}"
                 } else {
                     &sort_line
                 }),
                 Span::raw(" | "),
-                Span::styled("Limit: ", Style::default().fg(Color::Cyan)),
+                Span::styled("Limit: ", ctx.theme.label()),
                 Span::raw(if limit_line.is_empty() {
-                    "10"
+                    "none"
                 } else {
                     &limit_line
                 }),
@@ -0,0 +1,162 @@
+use color_eyre::eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, BorderType, Borders, Row, Table, TableState},
+};
+
+use super::super::{
+    context::MongoContext,
+    format::{bson_type_name, get_nested},
+    pane_id::PaneId,
+    registry::Pane,
+};
+use super::{clicked_line, scroll_key};
+use crate::action::Action;
+
+pub struct SchemaPane {
+    id: PaneId,
+    state: TableState,
+}
+
+impl SchemaPane {
+    pub fn new(id: PaneId) -> Self {
+        Self {
+            id,
+            state: TableState::default(),
+        }
+    }
+}
+
+impl Pane for SchemaPane {
+    fn id(&self) -> PaneId {
+        self.id
+    }
+
+    fn name(&self) -> &'static str {
+        "Schema"
+    }
+
+    fn get_shortcuts(&self) -> Vec<(&'static str, &'static str)> {
+        vec![("j/k", "Nav")]
+    }
+
+    fn clear_selection(&mut self, _ctx: &mut MongoContext) {
+        self.state.select(None);
+    }
+
+    fn update(&mut self, action: Action, ctx: &mut MongoContext) -> Result<Option<Action>> {
+        if let Action::SchemaLoaded(_) = action {
+            self.state
+                .select(if ctx.schema.is_empty() { None } else { Some(0) });
+        }
+        Ok(None)
+    }
+
+    fn handle_key_event(
+        &mut self,
+        key: KeyEvent,
+        ctx: &mut MongoContext,
+    ) -> Result<Option<Action>> {
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                let last = ctx.schema.len().saturating_sub(1);
+                let i = self.state.selected().map_or(0, |i| (i + 1).min(last));
+                self.state.select(Some(i));
+                return Ok(Some(Action::Render));
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                let i = self.state.selected().map_or(0, |i| i.saturating_sub(1));
+                self.state.select(Some(i));
+                return Ok(Some(Action::Render));
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        mouse: MouseEvent,
+        area: Rect,
+        ctx: &mut MongoContext,
+    ) -> Result<Option<Action>> {
+        if let Some(key) = scroll_key(&mouse) {
+            return self.handle_key_event(key, ctx);
+        }
+        // One header line above the rows
+        let Some(line) = clicked_line(&mouse, area, 1) else {
+            return Ok(None);
+        };
+        let idx = self.state.offset() + line;
+        if idx >= ctx.schema.len() {
+            return Ok(None);
+        }
+        self.state.select(Some(idx));
+        Ok(Some(Action::Render))
+    }
+
+    fn draw(
+        &mut self,
+        f: &mut Frame,
+        area: Rect,
+        is_active: bool,
+        ctx: &MongoContext,
+    ) -> Result<()> {
+        let block = Block::default()
+            .title(ctx.theme.title(is_active, "[5] Schema"))
+            .title_bottom(
+                Line::from("j/k: Nav")
+                    .style(ctx.theme.hint(is_active))
+                    .alignment(Alignment::Center),
+            )
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(ctx.theme.border(is_active));
+
+        // Types and presence are measured on the documents of the current page
+        let total = ctx.documents.len();
+        let rows = ctx.schema.iter().map(|(field, inferred)| {
+            let mut types: Vec<&str> = vec![];
+            let mut present = 0usize;
+            for value in ctx
+                .documents
+                .iter()
+                .filter_map(|doc| get_nested(doc, field))
+            {
+                present += 1;
+                let name = bson_type_name(value);
+                if !types.contains(&name) {
+                    types.push(name);
+                }
+            }
+            // Fields absent from this page keep the type seen in the sample
+            if types.is_empty() {
+                types.push(inferred);
+            }
+            let presence = (present * 100)
+                .checked_div(total)
+                .map_or_else(|| "-".to_string(), |pct| format!("{}%", pct));
+            Row::new(vec![field.clone(), types.join("|"), presence])
+        });
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(45),
+                Constraint::Percentage(35),
+                Constraint::Percentage(20),
+            ],
+        )
+        .header(
+            Row::new(vec!["Field", "Type", "Seen"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(block)
+        .row_highlight_style(ctx.theme.highlight())
+        .highlight_symbol(ctx.theme.highlight_symbol());
+
+        f.render_stateful_widget(table, area, &mut self.state);
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use color_eyre::eyre::Result;
-use crossterm::event::KeyEvent;
-use ratatui::layout::Rect;
+use crossterm::event::{KeyEvent, MouseEvent};
+use ratatui::layout::{Position, Rect};
 use ratatui::Frame;
 use std::collections::HashMap;
 
@@ -24,6 +24,23 @@ pub trait Pane {
     fn update(&mut self, _action: Action, _ctx: &mut MongoContext) -> Result<Option<Action>> {
         Ok(None)
     }
+    /// Clicks and wheel scrolls over the pane; `area` is where it was last
+    /// drawn.
+    fn handle_mouse_event(
+        &mut self,
+        _mouse: MouseEvent,
+        _area: Rect,
+        _ctx: &mut MongoContext,
+    ) -> Result<Option<Action>> {
+        Ok(None)
+    }
+    /// Last step of Esc: drop the pane's selection, if it has one.
+    fn clear_selection(&mut self, _ctx: &mut MongoContext) {}
+    /// True while the pane is taking text input, so global shortcuts must
+    /// not swallow its keys.
+    fn captures_input(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Default)]
@@ -31,6 +48,8 @@ pub struct PaneRegistry {
     panes: HashMap<PaneId, Box<dyn Pane>>,
     ordered_ids: Vec<PaneId>, // Defines navigation cycle order
     active_pane: Option<PaneId>,
+    // Where each pane was last drawn, for mouse hit-testing
+    areas: HashMap<PaneId, Rect>,
 }
 
 impl PaneRegistry {
@@ -82,6 +101,12 @@ impl PaneRegistry {
         }
     }
 
+    pub fn active_pane_captures_input(&self) -> bool {
+        self.active_pane
+            .and_then(|id| self.panes.get(&id))
+            .is_some_and(|pane| pane.captures_input())
+    }
+
     pub fn set_active(&mut self, id: PaneId) {
         if self.panes.contains_key(&id) {
             self.active_pane = Some(id);
@@ -100,6 +125,42 @@ impl PaneRegistry {
         }
     }
 
+    /// Draws a pane and remembers its area for mouse events.
+    pub fn draw(
+        &mut self,
+        id: PaneId,
+        f: &mut Frame,
+        area: Rect,
+        ctx: &MongoContext,
+    ) -> Result<()> {
+        let is_active = self.active_pane == Some(id);
+        self.areas.insert(id, area);
+        if let Some(pane) = self.panes.get_mut(&id) {
+            pane.draw(f, area, is_active, ctx)?;
+        }
+        Ok(())
+    }
+
+    /// The pane drawn under the given cell, if any.
+    pub fn pane_at(&self, column: u16, row: u16) -> Option<PaneId> {
+        self.areas
+            .iter()
+            .find(|(_, area)| area.contains(Position::new(column, row)))
+            .map(|(id, _)| *id)
+    }
+
+    pub fn handle_mouse_event(
+        &mut self,
+        id: PaneId,
+        mouse: MouseEvent,
+        ctx: &mut MongoContext,
+    ) -> Result<Option<Action>> {
+        let (Some(pane), Some(area)) = (self.panes.get_mut(&id), self.areas.get(&id)) else {
+            return Ok(None);
+        };
+        pane.handle_mouse_event(mouse, *area, ctx)
+    }
+
     pub fn update_all(&mut self, action: Action, ctx: &mut MongoContext) -> Result<()> {
         // Broadcast updates to all panes
         for pane in self.panes.values_mut() {
@@ -0,0 +1,57 @@
+//! Connections open side by side, each with its own client, data and pane
+//! state.
+
+use mongo_core::MongoCore;
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+use super::{context::MongoContext, registry::PaneRegistry};
+use crate::action::Action;
+
+/// One open connection. The active tab's context and panes live in the
+/// viewer itself; the others are parked here until switched to.
+pub struct Tab {
+    pub id: usize,
+    pub parked: Option<(MongoContext, PaneRegistry)>,
+    /// Results that arrived while the tab was in the background, replayed
+    /// once it is active again.
+    pub pending: Vec<Action>,
+}
+
+impl Tab {
+    pub fn new(id: usize, parked: Option<(MongoContext, PaneRegistry)>) -> Self {
+        Self {
+            id,
+            parked,
+            pending: vec![],
+        }
+    }
+}
+
+/// Sender for the background tasks of tab `id`. What goes through it reaches
+/// the viewer wrapped in `ForTab`, so a result lands in the tab that asked
+/// for it even after switching away.
+pub fn tab_sender(id: usize, tx: UnboundedSender<Action>) -> UnboundedSender<Action> {
+    let (tab_tx, mut rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(action) = rx.recv().await {
+            if tx.send(Action::ForTab(id, Box::new(action))).is_err() {
+                break;
+            }
+        }
+    });
+    tab_tx
+}
+
+/// Name in the tab bar: the saved connection's, else the server's hosts.
+pub fn tab_label(context: &MongoContext) -> String {
+    let Some(uri) = &context.connected_uri else {
+        return "New".to_string();
+    };
+    context
+        .connections
+        .iter()
+        .find(|c| c.uri == *uri)
+        .map(|c| c.name.clone())
+        .or_else(|| MongoCore::uri_hosts(uri))
+        .unwrap_or_else(|| "?".to_string())
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use mongo_core::bson::Bson;
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::{app::Mode, config::Styles};
+
+/// Resolves the styles used across the viewer. Each style can be overridden
+/// from the `styles` section of the config under the name passed to
+/// [`Theme::color`]. In no-color mode the config is ignored and every style
+/// keeps the terminal's own colors, relying on modifiers and `>` markers to
+/// show focus and selection.
+#[derive(Clone, Debug, Default)]
+pub struct Theme {
+    pub no_color: bool,
+    styles: HashMap<String, Style>,
+}
+
+impl Theme {
+    pub fn new(no_color: bool, styles: &Styles) -> Self {
+        Self {
+            no_color,
+            styles: styles.0.get(&Mode::Home).cloned().unwrap_or_default(),
+        }
+    }
+
+    /// The configured style for `name`, else the built-in default.
+    fn color(&self, name: &str, style: Style, plain: Style) -> Style {
+        if self.no_color {
+            plain
+        } else {
+            self.styles.get(name).copied().unwrap_or(style)
+        }
+    }
+
+    /// Pane border, highlighted when the pane has focus.
+    pub fn border(&self, is_active: bool) -> Style {
+        if !is_active {
+            return self.color("border", Style::default(), Style::default());
+        }
+        self.color(
+            "border_active",
+            Style::default().fg(Color::Yellow),
+            Style::default().add_modifier(Modifier::BOLD),
+        )
+    }
+
+    /// Pane title, prefixed with `>` in no-color mode when focused.
+    pub fn title(&self, is_active: bool, title: &str) -> String {
+        if self.no_color && is_active {
+            format!("> {}", title)
+        } else {
+            title.to_string()
+        }
+    }
+
+    /// Shortcut hints in a pane's bottom title: bright when the pane has
+    /// focus, dimmed otherwise.
+    pub fn hint(&self, is_active: bool) -> Style {
+        if is_active {
+            self.color("hint", Style::default().fg(Color::White), Style::default())
+        } else {
+            self.muted()
+        }
+    }
+
+    /// Selected row or item.
+    pub fn highlight(&self) -> Style {
+        self.color(
+            "highlight",
+            Style::default().bg(Color::Blue),
+            Style::default().add_modifier(Modifier::REVERSED),
+        )
+    }
+
+    /// Lines picked in the JSON viewer, and the selected row of a pane
+    /// that has lost focus.
+    pub fn selection(&self) -> Style {
+        self.color(
+            "selection",
+            Style::default().bg(Color::DarkGray),
+            Style::default().add_modifier(Modifier::REVERSED | Modifier::DIM),
+        )
+    }
+
+    /// The active table cell, bright while its pane has focus and marked
+    /// more quietly otherwise.
+    pub fn cell(&self, is_active: bool) -> Style {
+        if is_active {
+            self.color(
+                "cell_active",
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+                Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            )
+        } else {
+            self.color(
+                "cell",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::UNDERLINED),
+                Style::default().add_modifier(Modifier::UNDERLINED),
+            )
+        }
+    }
+
+    /// Marker drawn before the selected row; only needed without colors.
+    pub fn highlight_symbol(&self) -> &'static str {
+        if self.no_color {
+            "> "
+        } else {
+            ""
+        }
+    }
+
+    /// Emphasized text such as the active field or a filter match.
+    pub fn accent(&self) -> Style {
+        self.color(
+            "accent",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+            Style::default().add_modifier(Modifier::BOLD),
+        )
+    }
+
+    /// Field labels and column headers.
+    pub fn label(&self) -> Style {
+        self.color("label", Style::default().fg(Color::Cyan), Style::default())
+    }
+
+    /// Secondary information: annotations, counts, notices.
+    pub fn muted(&self) -> Style {
+        self.color(
+            "muted",
+            Style::default().fg(Color::DarkGray),
+            Style::default().add_modifier(Modifier::DIM),
+        )
+    }
+
+    /// Plain values in the document tree, colored by type.
+    pub fn json_value(&self, value: &Bson) -> Style {
+        let (name, color) = match value {
+            Bson::String(_) => ("json_string", Color::Green),
+            Bson::Int32(_) | Bson::Int64(_) | Bson::Double(_) | Bson::Decimal128(_) => {
+                ("json_number", Color::LightBlue)
+            }
+            Bson::Boolean(_) => ("json_bool", Color::Magenta),
+            Bson::Null | Bson::Undefined => ("json_null", Color::DarkGray),
+            Bson::ObjectId(_) => ("json_object_id", Color::Yellow),
+            _ => return Style::default(),
+        };
+        self.color(name, Style::default().fg(color), Style::default())
+    }
+
+    pub fn error(&self) -> Style {
+        self.color(
+            "error",
+            Style::default().fg(Color::Red),
+            Style::default().add_modifier(Modifier::BOLD),
+        )
+    }
+
+    pub fn success(&self) -> Style {
+        self.color(
+            "success",
+            Style::default().fg(Color::Green),
+            Style::default(),
+        )
+    }
+}
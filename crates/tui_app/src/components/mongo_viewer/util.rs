@@ -0,0 +1,277 @@
+//! Parsing helpers shared by the query inputs and document editors.
+
+use anyhow::{anyhow, bail};
+use mongo_core::bson::{doc, oid::ObjectId, Bson, DateTime, Decimal128, Document};
+
+use super::defs::{FilterOp, FilterRow};
+
+/// Parses MongoDB Extended JSON (canonical or relaxed) into a document, so
+/// inputs can use `{"$oid": ...}`, `{"$date": ...}`, `{"$numberLong": ...}`
+/// and friends. Key order is kept as typed.
+pub fn parse_extended_json(input: &str) -> anyhow::Result<Document> {
+    let value: serde_json::Value =
+        serde_json::from_str(input).map_err(|e| anyhow!("JSON syntax error: {}", e))?;
+    document_from_value(value)
+}
+
+/// Converts an already parsed JSON value holding an Extended JSON object.
+pub fn document_from_value(value: serde_json::Value) -> anyhow::Result<Document> {
+    if !value.is_object() {
+        bail!("Expected a JSON object");
+    }
+    match Bson::try_from(value) {
+        Ok(Bson::Document(doc)) => Ok(doc),
+        // An object that is itself a single extended value, e.g. {"$oid": ...}
+        Ok(other) => bail!("Expected a document, got {:?}", other.element_type()),
+        Err(e) => bail!("Unsupported extended JSON type: {}", e),
+    }
+}
+
+/// Sort directions must be `1` or `-1`; `{"$meta": ...}` is allowed for
+/// text score sorts.
+pub fn check_sort(sort: &Document) -> anyhow::Result<()> {
+    for (field, value) in sort {
+        let ok = match value {
+            Bson::Document(doc) => doc.keys().all(|k| k == "$meta"),
+            other => matches!(as_number(other), Some(n) if n == 1.0 || n == -1.0),
+        };
+        if !ok {
+            bail!("Sort on \"{}\" must be 1 or -1, got {}", field, value);
+        }
+    }
+    Ok(())
+}
+
+/// An update document must hold operators such as `$set` or `$inc` at the
+/// top level; a plain document would replace each match instead.
+pub fn check_update(update: &Document) -> anyhow::Result<()> {
+    if update.is_empty() {
+        bail!("Update is empty");
+    }
+    if let Some(key) = update.keys().find(|k| !k.starts_with('$')) {
+        bail!("Update must use operators like $set, got field \"{}\"", key);
+    }
+    Ok(())
+}
+
+/// Commands that remove data, users or indexes; the command console asks
+/// twice before running them.
+const DESTRUCTIVE_COMMANDS: &[&str] = &[
+    "drop",
+    "dropDatabase",
+    "dropIndexes",
+    "deleteIndexes",
+    "dropSearchIndex",
+    "delete",
+    "dropUser",
+    "dropAllUsersFromDatabase",
+    "dropRole",
+    "dropAllRolesFromDatabase",
+    "shutdown",
+];
+
+/// Whether a database command is destructive. The command name is its first
+/// key, compared case-insensitively; `findAndModify` counts only with
+/// `remove` set.
+pub fn is_destructive_command(command: &Document) -> bool {
+    let Some(name) = command.keys().next() else {
+        return false;
+    };
+    if name.eq_ignore_ascii_case("findAndModify") {
+        return match command.get("remove") {
+            Some(Bson::Boolean(remove)) => *remove,
+            Some(other) => as_number(other).is_some_and(|n| n != 0.0),
+            None => false,
+        };
+    }
+    DESTRUCTIVE_COMMANDS
+        .iter()
+        .any(|destructive| destructive.eq_ignore_ascii_case(name))
+}
+
+/// Projection values must be `0`/`1` or booleans. Operator documents such
+/// as `{"$slice": 5}` or `{"$elemMatch": ...}` pass through as is.
+pub fn check_projection(projection: &Document) -> anyhow::Result<()> {
+    for (field, value) in projection {
+        let ok = match value {
+            Bson::Boolean(_) => true,
+            Bson::Document(doc) => !doc.is_empty() && doc.keys().all(|k| k.starts_with('$')),
+            other => matches!(as_number(other), Some(n) if n == 0.0 || n == 1.0),
+        };
+        if !ok {
+            bail!(
+                "Projection of \"{}\" must be 0, 1, true or false, got {}",
+                field,
+                value
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Compiles guided filter rows into a filter document. Rows on distinct
+/// fields share one document; a field used twice moves everything under
+/// `$and`.
+pub fn compile_filter(fields: &[(String, String)], rows: &[FilterRow]) -> anyhow::Result<Document> {
+    let mut conditions = vec![];
+    for row in rows {
+        let Some((field, type_name)) = fields.get(row.field) else {
+            continue;
+        };
+        let value = row.value.trim();
+        let condition = match row.op {
+            FilterOp::Eq => typed_value(field, type_name, value)?,
+            FilterOp::Ne => Bson::Document(doc! { "$ne": typed_value(field, type_name, value)? }),
+            FilterOp::Gt => Bson::Document(doc! { "$gt": typed_value(field, type_name, value)? }),
+            FilterOp::Lt => Bson::Document(doc! { "$lt": typed_value(field, type_name, value)? }),
+            FilterOp::In => {
+                let values = value
+                    .split(',')
+                    .map(|item| typed_value(field, type_name, item.trim()))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                Bson::Document(doc! { "$in": values })
+            }
+            FilterOp::Regex => Bson::Document(doc! { "$regex": value }),
+            FilterOp::Exists => {
+                let exists = match value {
+                    "" | "true" => true,
+                    "false" => false,
+                    other => bail!("exists on \"{}\" takes true or false, got {}", field, other),
+                };
+                Bson::Document(doc! { "$exists": exists })
+            }
+        };
+        conditions.push(doc! { field.as_str(): condition });
+    }
+
+    let mut filter = Document::new();
+    let mut repeated = false;
+    for condition in &conditions {
+        for (field, value) in condition {
+            repeated |= filter.insert(field.clone(), value.clone()).is_some();
+        }
+    }
+    if repeated {
+        let all: Vec<Bson> = conditions.into_iter().map(Bson::Document).collect();
+        return Ok(doc! { "$and": all });
+    }
+    Ok(filter)
+}
+
+/// Parses `text` as the BSON type inferred for `field`, so a number field
+/// compares against a number and an `_id` against an ObjectId. Types
+/// without a natural text form take Extended JSON, else a plain string.
+fn typed_value(field: &str, type_name: &str, text: &str) -> anyhow::Result<Bson> {
+    let invalid = || anyhow!("\"{}\" is a {} field, got \"{}\"", field, type_name, text);
+    let value = match type_name {
+        "string" => Bson::String(text.to_string()),
+        "int" => match text.parse::<i32>() {
+            Ok(n) => Bson::Int32(n),
+            Err(_) => Bson::Int64(text.parse().map_err(|_| invalid())?),
+        },
+        "long" => Bson::Int64(text.parse().map_err(|_| invalid())?),
+        "double" => Bson::Double(text.parse().map_err(|_| invalid())?),
+        // Kept exact: a double would not equal the stored decimal
+        "decimal" => Bson::Decimal128(text.parse::<Decimal128>().map_err(|_| invalid())?),
+        "bool" => Bson::Boolean(text.parse().map_err(|_| invalid())?),
+        "objectId" => Bson::ObjectId(ObjectId::parse_str(text).map_err(|_| invalid())?),
+        "date" => {
+            // A bare day means its start, in UTC
+            let iso = if text.len() == 10 {
+                format!("{}T00:00:00Z", text)
+            } else {
+                text.to_string()
+            };
+            Bson::DateTime(DateTime::parse_rfc3339_str(&iso).map_err(|_| invalid())?)
+        }
+        _ if text == "null" => Bson::Null,
+        _ => serde_json::from_str::<serde_json::Value>(text)
+            .ok()
+            .and_then(|json| Bson::try_from(json).ok())
+            .unwrap_or_else(|| Bson::String(text.to_string())),
+    };
+    Ok(value)
+}
+
+fn as_number(value: &Bson) -> Option<f64> {
+    match value {
+        Bson::Int32(n) => Some(*n as f64),
+        Bson::Int64(n) => Some(*n as f64),
+        Bson::Double(n) => Some(*n),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compound_sort_keeps_key_order() {
+        let sort = parse_extended_json(r#"{ "score": -1, "name": 1 }"#).unwrap();
+        assert!(check_sort(&sort).is_ok());
+        let keys: Vec<&str> = sort.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["score", "name"]);
+    }
+
+    #[test]
+    fn decimal_fields_compare_against_a_decimal() {
+        let fields = vec![("price".to_string(), "decimal".to_string())];
+        let rows = [FilterRow {
+            field: 0,
+            op: FilterOp::Eq,
+            value: "1.1".to_string(),
+        }];
+        let filter = compile_filter(&fields, &rows).unwrap();
+        assert_eq!(
+            filter.get("price"),
+            Some(&Bson::Decimal128("1.1".parse().unwrap()))
+        );
+    }
+
+    #[test]
+    fn guided_filter_round_trips_through_its_json() {
+        let fields = vec![
+            ("_id".to_string(), "objectId".to_string()),
+            ("created".to_string(), "date".to_string()),
+        ];
+        let rows = [
+            FilterRow {
+                field: 0,
+                op: FilterOp::Eq,
+                value: "65f1a2b3c4d5e6f708192a3b".to_string(),
+            },
+            FilterRow {
+                field: 1,
+                op: FilterOp::Gt,
+                value: "2024-01-01".to_string(),
+            },
+        ];
+        let filter = compile_filter(&fields, &rows).unwrap();
+        let json = Bson::Document(filter.clone())
+            .into_relaxed_extjson()
+            .to_string();
+        assert_eq!(parse_extended_json(&json).unwrap(), filter);
+    }
+
+    #[test]
+    fn destructive_commands_are_matched_by_their_name() {
+        let destructive = |json: &str| is_destructive_command(&parse_extended_json(json).unwrap());
+        assert!(destructive(r#"{ "drop": "orders" }"#));
+        assert!(destructive(r#"{ "DropDatabase": 1 }"#));
+        assert!(destructive(r#"{ "dropIndexes": "orders", "index": "*" }"#));
+        assert!(destructive(r#"{ "delete": "orders", "deletes": [] }"#));
+        assert!(destructive(
+            r#"{ "findAndModify": "orders", "remove": true }"#
+        ));
+        assert!(!destructive(
+            r#"{ "findAndModify": "orders", "update": { "$set": { "a": 1 } } }"#
+        ));
+        // Field values and later keys do not count
+        assert!(!destructive(r#"{ "find": "dropped_items" }"#));
+        assert!(!destructive(
+            r#"{ "count": "orders", "query": { "delete": true } }"#
+        ));
+        assert!(!destructive("{}"));
+    }
+}
@@ -1,22 +1,96 @@
-use std::{collections::HashMap, env, path::PathBuf};
+use std::{collections::HashMap, env, path::PathBuf, str::FromStr};
 
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use directories::ProjectDirs;
 use lazy_static::lazy_static;
-use ratatui::style::Style;
+use ratatui::style::{Color, Modifier, Style};
 use serde::{de::Deserializer, Deserialize, Serialize};
 use tracing::error;
 
 use crate::{action::Action, app::Mode};
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Connection {
     pub name: String,
     pub uri: String,
+    /// Upper bound for the driver's connection pool; driver default if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_pool_size: Option<u32>,
+    /// Enable TLS even if the URI doesn't ask for it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub tls: bool,
+    /// Database holding the user's credentials, overriding `authSource`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_source: Option<String>,
+    /// Name reported to the server, overriding `appName`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_name: Option<String>,
+    /// Connections coming from the environment are never written to disk.
+    #[serde(skip)]
+    pub transient: bool,
+}
+
+impl Connection {
+    /// Builds a transient connection from `MONGODB_URI` (and optionally
+    /// `MONGODB_NAME`), if the variable is set.
+    pub fn from_env() -> Option<Self> {
+        let uri = env::var("MONGODB_URI")
+            .ok()
+            .filter(|u| !u.trim().is_empty())?;
+        let name = env::var("MONGODB_NAME")
+            .ok()
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| "MONGODB_URI".to_string());
+        Some(Self {
+            name,
+            uri,
+            max_pool_size: None,
+            tls: false,
+            auth_source: None,
+            app_name: None,
+            transient: true,
+        })
+    }
+
+    /// Overrides applied on top of the URI when connecting.
+    pub fn connect_options(&self) -> mongo_core::ConnectOptions {
+        mongo_core::ConnectOptions {
+            max_pool_size: self.max_pool_size,
+            tls: self.tls,
+            auth_source: self.auth_source.clone(),
+            app_name: self.app_name.clone(),
+        }
+    }
+}
+
+/// A query saved under a name. The inputs are kept as typed, so blank ones
+/// are left out of the file.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NamedQuery {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub filter: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sort: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub projection: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub limit: String,
+}
+
+/// Where copied values go; see `ClipboardBackend` in the viewer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardSetting {
+    /// The system clipboard, else OSC 52.
+    #[default]
+    Auto,
+    Osc52,
+    File,
 }
 
 /// The persisted application configuration.
-#[derive(Clone, Debug, Deserialize, Default, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct AppConfig {
     #[serde(default)]
     pub data_dir: PathBuf,
@@ -24,6 +98,107 @@ pub struct AppConfig {
     pub config_dir: PathBuf,
     #[serde(default)]
     pub connections: Vec<Connection>,
+    /// Queries saved by name from the query builder.
+    #[serde(default)]
+    pub saved_queries: Vec<NamedQuery>,
+    /// Number of documents fetched by the "sample" quick action.
+    #[serde(default = "default_sample_size")]
+    pub sample_size: i64,
+    /// Documents per page when browsing a collection.
+    #[serde(default = "default_page_size")]
+    pub page_size: usize,
+    /// Upper bound for the page size when adjusting it with `+`/`-`.
+    #[serde(default = "default_max_documents")]
+    pub max_documents: usize,
+    /// Seconds between re-runs of the query while auto-refresh is on.
+    #[serde(default = "default_auto_refresh_secs")]
+    pub auto_refresh_secs: u64,
+    /// Expand the first database (or the URI's database) once the list loads.
+    #[serde(default)]
+    pub auto_expand_first_db: bool,
+    /// Documents larger than this (in bytes of JSON) are shown without
+    /// syntax highlighting in the JSON viewer.
+    #[serde(default = "default_highlight_max_bytes")]
+    pub highlight_max_bytes: usize,
+    /// Whether `_id` is part of the default table columns.
+    #[serde(default = "default_true")]
+    pub show_id_column: bool,
+    /// Group the digits of integers in table cells, e.g. `1,200,000`.
+    #[serde(default)]
+    pub thousands_separators: bool,
+    /// Share of the width taken by the connections/databases sidebar.
+    /// Ctrl-Left/Right change it and write it back here.
+    #[serde(default = "default_sidebar_percent")]
+    pub sidebar_percent: u16,
+    /// Height of the query bar above the documents, borders included.
+    #[serde(default = "default_query_rows")]
+    pub query_rows: u16,
+    /// Where copied values go when pressing `y` and friends.
+    #[serde(default)]
+    pub clipboard: ClipboardSetting,
+    /// Capture the mouse for clicks and scrolling. Turning it off gives the
+    /// terminal's own text selection back.
+    #[serde(default = "default_true")]
+    pub mouse: bool,
+    /// Render without colors; set from `--no-color` or `NO_COLOR`.
+    #[serde(skip)]
+    pub no_color: bool,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            data_dir: PathBuf::default(),
+            config_dir: PathBuf::default(),
+            connections: Vec::new(),
+            saved_queries: Vec::new(),
+            sample_size: default_sample_size(),
+            page_size: default_page_size(),
+            max_documents: default_max_documents(),
+            auto_refresh_secs: default_auto_refresh_secs(),
+            auto_expand_first_db: false,
+            highlight_max_bytes: default_highlight_max_bytes(),
+            show_id_column: true,
+            thousands_separators: false,
+            sidebar_percent: default_sidebar_percent(),
+            query_rows: default_query_rows(),
+            clipboard: ClipboardSetting::Auto,
+            mouse: true,
+            no_color: false,
+        }
+    }
+}
+
+fn default_sample_size() -> i64 {
+    20
+}
+
+fn default_page_size() -> usize {
+    10
+}
+
+fn default_max_documents() -> usize {
+    1000
+}
+
+fn default_auto_refresh_secs() -> u64 {
+    5
+}
+
+fn default_highlight_max_bytes() -> usize {
+    256 * 1024
+}
+
+fn default_sidebar_percent() -> u16 {
+    20
+}
+
+fn default_query_rows() -> u16 {
+    6
+}
+
+fn default_true() -> bool {
+    true
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -93,13 +268,29 @@ impl Config {
         Ok(cfg)
     }
 
-    /// Persist the configuration to the OS config path.
+    /// Writes the config back to the file it was loaded from (see
+    /// [`config_file`]). Transient connections are left out, and sections this
+    /// struct doesn't write back (such as `keybindings`) are kept as they were.
     pub fn save(&self) -> color_eyre::Result<()> {
-        let config_dir = get_config_dir();
-        std::fs::create_dir_all(&config_dir)?;
-        let config_file = config_dir.join("config.json");
-        let json = serde_json::to_string_pretty(&self.config)?;
-        std::fs::write(config_file, json)?;
+        let path = config_file();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut config = self.config.clone();
+        config.connections.retain(|c| !c.transient);
+        let mut file = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok())
+            .filter(|value| value.is_object())
+            .unwrap_or_else(|| serde_json::json!({}));
+        if let (Some(file), serde_json::Value::Object(values)) =
+            (file.as_object_mut(), serde_json::to_value(&config)?)
+        {
+            file.extend(values);
+        }
+        let json = serde_json::to_string_pretty(&file)?;
+        std::fs::write(&path, json)
+            .map_err(|e| color_eyre::eyre::eyre!("{}: {}", path.display(), e))?;
         Ok(())
     }
 }
@@ -124,6 +315,16 @@ pub fn get_config_dir() -> PathBuf {
     }
 }
 
+/// The config file in effect: the local override if present, else the OS one.
+pub fn config_file() -> PathBuf {
+    let local_file = local_config_file();
+    if local_file.exists() {
+        local_file
+    } else {
+        os_config_file()
+    }
+}
+
 fn local_config_file() -> PathBuf {
     PathBuf::from(".mongo-tui.config.json")
 }
@@ -139,23 +340,201 @@ fn project_directory() -> Option<ProjectDirs> {
 #[derive(Clone, Debug, Default, Serialize)]
 pub struct KeyBindings(pub HashMap<Mode, HashMap<Vec<KeyEvent>, Action>>);
 
+impl KeyBindings {
+    /// Whether some binding of `mode` starts with `keys` but is longer.
+    pub fn is_prefix(&self, mode: Mode, keys: &[KeyEvent]) -> bool {
+        self.0.get(&mode).is_some_and(|bindings| {
+            bindings
+                .keys()
+                .any(|seq| seq.len() > keys.len() && seq.starts_with(keys))
+        })
+    }
+}
+
 impl<'de> Deserialize<'de> for KeyBindings {
-    fn deserialize<D>(_deserializer: D) -> color_eyre::Result<Self, D::Error>
+    /// Reads `{ "Mode": { "<key-seq>": "Action" } }`. Entries with an unknown
+    /// mode, key or action are skipped (and logged) so one typo doesn't
+    /// discard the rest of the config.
+    fn deserialize<D>(deserializer: D) -> color_eyre::Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        Ok(KeyBindings(HashMap::new()))
+        let parsed_map =
+            HashMap::<String, HashMap<String, serde_json::Value>>::deserialize(deserializer)?;
+
+        let mut keybindings: HashMap<Mode, HashMap<Vec<KeyEvent>, Action>> = HashMap::new();
+        for (mode, inner_map) in parsed_map {
+            let Ok(mode) = serde_json::from_value::<Mode>(serde_json::Value::String(mode.clone()))
+            else {
+                error!(mode, "unknown mode in keybindings");
+                continue;
+            };
+            let bindings = keybindings.entry(mode).or_default();
+            for (keys, action) in inner_map {
+                let sequence = match parse_key_sequence(&keys) {
+                    Ok(sequence) => sequence,
+                    Err(e) => {
+                        error!(keys, e, "invalid key sequence in keybindings");
+                        continue;
+                    }
+                };
+                match serde_json::from_value::<Action>(action) {
+                    Ok(action) => {
+                        bindings.insert(sequence, action);
+                    }
+                    Err(e) => error!(keys, %e, "unknown action in keybindings"),
+                }
+            }
+        }
+        Ok(KeyBindings(keybindings))
+    }
+}
+
+/// Parses a key sequence such as `q`, `gg`, `<ctrl-c>` or `<g><tab>`. Keys in
+/// angle brackets may carry `ctrl-`, `alt-` and `shift-` modifiers; any other
+/// character stands for itself.
+pub fn parse_key_sequence(raw: &str) -> Result<Vec<KeyEvent>, String> {
+    let mut sequence = vec![];
+    let mut rest = raw;
+    while let Some(c) = rest.chars().next() {
+        match rest.find('>') {
+            Some(end) if c == '<' && end > 1 => {
+                sequence.push(parse_key_event(&rest[1..end])?);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                sequence.push(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+                rest = &rest[c.len_utf8()..];
+            }
+        }
     }
+    if sequence.is_empty() {
+        return Err("empty key sequence".to_string());
+    }
+    Ok(sequence)
+}
+
+/// Parses the inside of a `<...>` key, e.g. `ctrl-c`, `tab` or `G`.
+fn parse_key_event(raw: &str) -> Result<KeyEvent, String> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut key = raw;
+    loop {
+        let lower = key.to_ascii_lowercase();
+        let prefix = ["ctrl-", "alt-", "shift-"]
+            .into_iter()
+            .find(|p| lower.starts_with(p) && key.len() > p.len());
+        match prefix {
+            Some("ctrl-") => modifiers.insert(KeyModifiers::CONTROL),
+            Some("alt-") => modifiers.insert(KeyModifiers::ALT),
+            Some(_) => modifiers.insert(KeyModifiers::SHIFT),
+            None => break,
+        }
+        key = &key[prefix.map_or(0, str::len)..];
+    }
+
+    let code = match key.to_ascii_lowercase().as_str() {
+        "esc" => KeyCode::Esc,
+        "enter" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "backtab" => {
+            modifiers.insert(KeyModifiers::SHIFT);
+            KeyCode::BackTab
+        }
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "space" => KeyCode::Char(' '),
+        "hyphen" | "minus" => KeyCode::Char('-'),
+        "lt" => KeyCode::Char('<'),
+        "gt" => KeyCode::Char('>'),
+        lower => {
+            let mut chars = key.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                _ => match lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key: {}", key)),
+                },
+            }
+        }
+    };
+    Ok(KeyEvent::new(code, modifiers))
 }
 
 #[derive(Clone, Debug, Default, Serialize)]
 pub struct Styles(pub HashMap<Mode, HashMap<String, Style>>);
 
 impl<'de> Deserialize<'de> for Styles {
-    fn deserialize<D>(_deserializer: D) -> Result<Self, D::Error>
+    /// Reads `{ "Mode": { "<name>": "<style>" } }`, where a style is written
+    /// like `bold yellow on blue` (see [`parse_style`]). Invalid entries are
+    /// skipped and logged.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        Ok(Styles(HashMap::new()))
+        let parsed_map = HashMap::<String, HashMap<String, String>>::deserialize(deserializer)?;
+
+        let mut styles: HashMap<Mode, HashMap<String, Style>> = HashMap::new();
+        for (mode, inner_map) in parsed_map {
+            let Ok(mode) = serde_json::from_value::<Mode>(serde_json::Value::String(mode.clone()))
+            else {
+                error!(mode, "unknown mode in styles");
+                continue;
+            };
+            let mode_styles = styles.entry(mode).or_default();
+            for (name, raw) in inner_map {
+                match parse_style(&raw) {
+                    Ok(style) => {
+                        mode_styles.insert(name, style);
+                    }
+                    Err(e) => error!(name, e, "invalid style"),
+                }
+            }
+        }
+        Ok(Styles(styles))
     }
 }
+
+/// Parses a style such as `yellow`, `bold black on yellow` or `#ff8800 on 236`.
+/// The first color is the foreground, a color after `on` the background, and
+/// modifier names (`bold`, `dim`, `italic`, `underlined`, `reversed`) may
+/// appear anywhere.
+pub fn parse_style(raw: &str) -> Result<Style, String> {
+    let mut style = Style::default();
+    let mut words = raw.split_whitespace();
+    while let Some(word) = words.next() {
+        let modifier = match word.to_ascii_lowercase().as_str() {
+            "bold" => Some(Modifier::BOLD),
+            "dim" => Some(Modifier::DIM),
+            "italic" => Some(Modifier::ITALIC),
+            "underlined" => Some(Modifier::UNDERLINED),
+            "reversed" => Some(Modifier::REVERSED),
+            "on" => {
+                let color = words.next().ok_or("missing color after 'on'")?;
+                style = style.bg(parse_color(color)?);
+                None
+            }
+            _ => {
+                style = style.fg(parse_color(word)?);
+                None
+            }
+        };
+        if let Some(modifier) = modifier {
+            style = style.add_modifier(modifier);
+        }
+    }
+    Ok(style)
+}
+
+/// A named color (`yellow`, `lightblue`), `#rrggbb` or a 256-color index.
+fn parse_color(raw: &str) -> Result<Color, String> {
+    Color::from_str(raw).map_err(|_| format!("unknown color: {}", raw))
+}
@@ -1,11 +1,20 @@
 use crate::config::get_data_dir;
 use color_eyre::eyre::Result;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
 use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
+/// Bytes read from the end of the log at most when tailing it.
+const TAIL_MAX_BYTES: u64 = 512 * 1024;
+
+pub fn log_file() -> PathBuf {
+    get_data_dir().join("app.log")
+}
+
 pub fn init() -> Result<()> {
     let directory = get_data_dir();
     std::fs::create_dir_all(&directory)?;
-    let log_file = std::fs::File::create(directory.join("app.log"))?;
+    let log_file = std::fs::File::create(log_file())?;
     let file_layer = fmt::layer().with_writer(log_file).with_ansi(false);
 
     tracing_subscriber::registry()
@@ -15,3 +24,21 @@ pub fn init() -> Result<()> {
 
     Ok(())
 }
+
+/// Last `max_lines` lines of the log, newest first. Only the end of the
+/// file is read, so a long session's log stays cheap to open.
+pub fn tail(max_lines: usize) -> std::io::Result<Vec<String>> {
+    let mut file = std::fs::File::open(log_file())?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(TAIL_MAX_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::with_capacity((len - start) as usize);
+    file.read_to_end(&mut bytes)?;
+    let text = String::from_utf8_lossy(&bytes);
+    let mut lines = text.lines();
+    // The first line is likely cut in the middle when starting mid-file
+    if start > 0 {
+        lines.next();
+    }
+    Ok(lines.rev().take(max_lines).map(str::to_string).collect())
+}
@@ -29,7 +29,14 @@ async fn main() -> Result<()> {
     crate::logging::init()?;
 
     let args = Cli::parse();
-    let mut app = App::new(args.tick_rate, args.frame_rate)?;
+    // https://no-color.org: any non-empty value disables colors
+    let no_color = args.no_color || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let mut app = App::new(
+        args.tick_rate,
+        args.frame_rate,
+        no_color,
+        args.startup_target(),
+    )?;
     app.run().await?;
     Ok(())
 }